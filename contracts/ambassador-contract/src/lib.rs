@@ -4,8 +4,12 @@ use soroban_sdk::{
     Env, Address,
     BytesN,
     symbol_short,
-    Vec,
+    Vec, Map,
     String,
+    IntoVal, Symbol, TryFromVal, Val,
+    Bytes,
+    xdr::ToXdr,
+    token,
 };
 
 // --- Custom Error Definitions ---
@@ -19,6 +23,41 @@ pub enum Error {
     IncorrectHash = 4,
     AlreadyRegistered = 5,
     InvalidNickname = 6,
+    NotRegistered = 7,
+    InvalidName = 8,
+    NicknameTaken = 9,
+    ProfileNotFound = 10,
+    ContractPaused = 11,
+    SessionExpired = 12,
+    SessionFull = 13,
+    InvalidConfig = 14,
+    CannotRemoveLastAdmin = 15,
+    NotAdmin = 16,
+    LimitTooLarge = 17,
+    NonceReused = 18,
+    NoCommitment = 19,
+    AdminCannotAttend = 20,
+    ProfileRequired = 21,
+    InvalidAvatarUri = 22,
+    CannotPurgeActive = 23,
+    NoPendingAdmin = 24,
+    ProfileUpdateTooSoon = 25,
+    InvalidAdmin = 26,
+    InvalidWeight = 27,
+    SessionStillActive = 28,
+    AlreadyClaimed = 29,
+    RewardTransferFailed = 30,
+    NotModerator = 31,
+    InvalidHash = 32,
+    MustAttendFirst = 33,
+    InvalidMemo = 34,
+    HashAlreadyUsed = 35,
+    CheckinTooSoon = 36,
+    ProfileAlreadyExists = 37,
+    NoRewardToken = 38,
+    InvalidBio = 39,
+    ProfilesLocked = 40,
+    CheckedInActiveEvent = 41,
 }
 
 // --- User Profile Struct ---
@@ -27,6 +66,78 @@ pub enum Error {
 pub struct UserProfile {
     pub nickname: String,
     pub registered_at: u32,
+    pub avatar_uri: String,
+    pub last_updated: u32,
+    pub registered_timestamp: u64,
+    pub bio: String,
+}
+
+// --- Session Info Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionInfo {
+    pub name: String,
+    pub created_at: u32,
+    pub hash: BytesN<32>,
+    pub weight: u32,
+    pub event_id: BytesN<32>,
+    pub memo: String,
+    pub created_by: Option<Address>,
+}
+
+// --- TTL Configuration Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub threshold: u32,
+    pub session_bump: u32,
+    pub profile_bump: u32,
+}
+
+// --- Presence Record Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PresenceRecord {
+    pub at: u32,
+    pub marked_by: Option<Address>,
+}
+
+// --- Session State Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionState {
+    pub active_hash: Option<BytesN<32>>,
+    pub paused: bool,
+    pub attendee_count: u32,
+    pub expires_at: Option<u32>,
+    pub open: bool,
+}
+
+// --- Token Reward Configuration Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardConfig {
+    pub token: Address,
+    pub amount: i128,
+    pub revert_on_failure: bool,
+}
+
+// --- Points Decay Configuration Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecayConfig {
+    pub rate_percent: u32,
+    pub period: u32,
+}
+
+// --- Global Statistics Struct ---
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub session_count: u32,
+    pub total_unique_users: u32,
+    pub total_checkins_all: u32,
+    pub active: bool,
 }
 
 // --- Storage Key Definitions ---
@@ -37,6 +148,66 @@ pub enum StorageKey {
     ActiveHash,
     Presence(BytesN<32>, Address),
     UserProfile(Address),
+    AttendanceCount(BytesN<32>),
+    SessionList,
+    SessionInfo(BytesN<32>),
+    ClosedSession(BytesN<32>),
+    SessionIndex,
+    LastSessionIndex(Address),
+    Streak(Address),
+    NicknameOwner(String),
+    Paused,
+    SessionExpiry(BytesN<32>),
+    Attendees(BytesN<32>),
+    NicknameBounds,
+    Admins,
+    Points(Address),
+    PointsPerCheckin,
+    Participants,
+    Commitment(Address),
+    UsedNonce(BytesN<32>),
+    TtlConfig,
+    AdminCanAttend,
+    RequireProfile,
+    TotalCheckins(Address),
+    PendingAdmin,
+    ProfileCooldown,
+    SessionCapacity(BytesN<32>),
+    PresenceNickname(BytesN<32>, Address),
+    StrictSessions,
+    Claimed(BytesN<32>, Address),
+    SessionReward,
+    RewardToken,
+    BadgeContract,
+    Badge(BytesN<32>, Address),
+    DefaultSessionDuration,
+    Moderators,
+    ProfileRequiresAttendance,
+    DailyCount(u32),
+    ActiveHashLiveUntil,
+    LastCheckin(Address),
+    CheckinCooldown,
+    DecayConfig,
+    LastDecay(Address),
+    MaxSessionsRetained,
+    UniqueUsers,
+    Archived(BytesN<32>),
+    OptedOut(Address),
+}
+
+// --- Extended Storage Keys ---
+// `StorageKey` is at the contract spec's 50-variant limit, so newer keys go here. Keys are
+// stored by variant name, so a name must never repeat one of `StorageKey`'s.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StorageKeyExt {
+    AdminHistory,
+    TotalCheckinsAll,
+    EventPrefix,
+    Receipt(BytesN<32>, Address),
+    ProfilesLocked,
+    Rewarded(BytesN<32>, Address),
+    PurgeQueue,
 }
 
 // --- Contract Definition ---
@@ -47,6 +218,10 @@ pub struct AttendanceContract;
 #[contractimpl]
 impl AttendanceContract {
 
+    // Contract interface version, bumped on every release that changes the public API
+    pub const VERSION: u32 = 2;
+
+    // Default TTL values, overridable by the admin through `set_ttl_config`
     // TTL Threshold: ~7 days
     const TTL_THRESHOLD: u32 = 120_960;
     // Bump for instance data and sessions: ~30 days
@@ -54,247 +229,3547 @@ impl AttendanceContract {
     // Bump for user profiles: ~90 days
     const TTL_BUMP_90D: u32 = 1_555_200;
 
-    /// Initializes the contract, setting the administrator.
-    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
-        if env.storage().instance().has(&StorageKey::Admin) {
+    // Maximum number of session hashes kept in the session list
+    const MAX_SESSIONS: u32 = 100;
+    // Maximum number of attendees tracked per session
+    const MAX_ATTENDEES: u32 = 1_000;
+    // Default and absolute nickname length bounds
+    const DEFAULT_NICKNAME_MIN: u32 = 3;
+    const DEFAULT_NICKNAME_MAX: u32 = 32;
+    const NICKNAME_LIMIT: u32 = 64;
+    // Points awarded per first-time check-in unless the admin configures otherwise
+    const DEFAULT_POINTS_PER_CHECKIN: u64 = 10;
+    // Maximum number of users accepted by a single `admin_preregister` call: each one writes up
+    // to 3 entries on top of about 5 shared ones, within the ~50 write entries of a transaction
+    const MAX_PREREGISTER: u32 = 12;
+    // Maximum number of entries accepted by a single `register_batch` call: a check-in with every
+    // reward enabled writes about 12 entries, plus about 7 shared ones
+    const MAX_REGISTER_BATCH: u32 = 3;
+    // Maximum number of addresses tracked for the leaderboard
+    const MAX_PARTICIPANTS: u32 = 1_000;
+    // Maximum page size for leaderboard queries
+    const MAX_LEADERBOARD_PAGE: u32 = 50;
+    // Maximum page size for attendee list queries
+    const MAX_ATTENDEES_PAGE: u32 = 100;
+    const MAX_SESSION_MEMO: u32 = 64;
+    const SECONDS_PER_DAY: u64 = 86_400;
+    const MAX_ATTENDANCE_QUERY: u32 = 50;
+    // Renaming writes the profile and both nickname index entries, so 15 entries stay below ~50 writes
+    const MAX_PROFILE_BATCH: u32 = 15;
+    const MAX_DECAY_PERIODS: u32 = 100;
+    // All-zero account standing in for opted-out users in check-in events
+    const ANONYMOUS_ACCOUNT: &'static str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+    // Maximum length of a profile avatar / metadata URI
+    const MAX_AVATAR_URI: u32 = 200;
+    // Maximum length of a profile bio
+    const MAX_BIO: u32 = 160;
+    // Minimum ledgers between profile updates unless the admin configures otherwise (~1 day)
+    const DEFAULT_PROFILE_COOLDOWN: u32 = 17_280;
+    // Allowed range for the points multiplier of a session
+    const DEFAULT_SESSION_WEIGHT: u32 = 1;
+    const MAX_SESSION_WEIGHT: u32 = 100;
+    // Bonus points for claiming a session reward unless the admin configures otherwise
+    const DEFAULT_SESSION_REWARD: u64 = 5;
+    // Maximum number of primary admins kept in the admin history
+    const MAX_ADMIN_HISTORY: u32 = 50;
+    // Maximum number of sessions evicted by a single `start_session`
+    const MAX_EVICTIONS_PER_CALL: u32 = 5;
+    // Maximum number of evicted events awaiting `purge_evicted`
+    const MAX_PURGE_QUEUE: u32 = 500;
+    // Maximum number of attendees purged by a single `purge_evicted` call, about 4 removals each
+    const MAX_PURGE_CHUNK: u32 = 10;
+
+    /// Initializes the contract, setting the administrator. An `event_prefix` is prepended to
+    /// every event's topics so indexers can tell deployments of the same WASM apart.
+    pub fn initialize(env: Env, admin: Address, event_prefix: Option<Symbol>) -> Result<(), Error> {
+        if Self::load_admins(&env).is_ok() {
             return Err(Error::AlreadyInitialized);
         }
 
+        // The contract cannot act as its own admin: it has no key to authorize admin calls with
+        if admin == env.current_contract_address() {
+            return Err(Error::InvalidAdmin);
+        }
+
         admin.require_auth();
-        env.storage().instance().set(&StorageKey::Admin, &admin);
+        env.storage().instance().set(&StorageKey::Admins, &Vec::from_array(&env, [admin.clone()]));
+        env.storage().instance().set(
+            &StorageKey::NicknameBounds,
+            &(Self::DEFAULT_NICKNAME_MIN, Self::DEFAULT_NICKNAME_MAX)
+        );
+        env.storage().instance().set(&StorageKey::TtlConfig, &Self::default_ttl_config());
+        if let Some(prefix) = event_prefix {
+            env.storage().instance().set(&StorageKeyExt::EventPrefix, &prefix);
+        }
 
-        env.storage().instance().extend_ttl(Self::TTL_THRESHOLD, Self::TTL_BUMP_30D);
+        Self::push_admin_history(&env, &admin);
 
-        env.events().publish(
-            (symbol_short!("init"),), // Topic
-                             (admin,)                   // Data (as a Tuple)
-        );
+        Self::bump_instance(&env);
+
+        Self::emit_init(&env, admin);
 
         Ok(())
     }
 
-    /// (Admin only) Sets the active attendance hash, starting a new session.
-    pub fn set_hash(env: Env, new_hash: BytesN<32>) -> Result<(), Error> {
-        let admin: Address = env.storage().instance()
-        .get(&StorageKey::Admin)
-        .ok_or(Error::NotInitialized)?;
+    /// Idempotent `initialize` for deploy scripts: initializes with `admin` and returns it, or
+    /// returns the current primary admin untouched if the contract is already initialized.
+    pub fn initialize_or_get(env: Env, admin: Address, event_prefix: Option<Symbol>) -> Result<Address, Error> {
+        if let Ok(current_admin) = Self::require_initialized(&env) {
+            return Ok(current_admin);
+        }
 
-        admin.require_auth();
+        Self::initialize(env, admin.clone(), event_prefix)?;
 
-        env.storage().persistent().set(&StorageKey::ActiveHash, &new_hash);
+        Ok(admin)
+    }
 
-        env.storage().persistent().extend_ttl(
-            &StorageKey::ActiveHash,
-            Self::TTL_THRESHOLD,
-            Self::TTL_BUMP_30D
-        );
+    /// (Admin or moderator) Sets the active attendance hash, starting a new session.
+    pub fn set_hash(env: Env, admin: Address, new_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
 
-        env.events().publish(
-            (symbol_short!("new_sess"),),
-                             (new_hash,)
-        );
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash,
+            String::from_str(&env, ""),
+        )?;
 
         Ok(())
     }
 
-    /// (User function) Registers the caller's presence for the active session.
-    pub fn register(env: Env, user: Address, submitted_hash: BytesN<32>) -> Result<(), Error> {
-        user.require_auth();
+    /// (Admin only) Like `set_hash`, but with `allow_reuse` set it also accepts a hash that an
+    /// earlier session used. The new session then shares that session's presence entries.
+    pub fn set_hash_force(env: Env, admin: Address, new_hash: BytesN<32>, allow_reuse: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
 
-        let stored_hash: BytesN<32> = env
+        if allow_reuse {
+            // `start_session` treats a hash without metadata as unused
+            env.storage().persistent().remove(&StorageKey::SessionInfo(new_hash.clone()));
+        }
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash,
+            String::from_str(&env, ""),
+        )?;
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a new session with a human-readable name.
+    pub fn set_hash_named(env: Env, admin: Address, new_hash: BytesN<32>, name: String) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        if name.len() < 3 || name.len() > 32 {
+            return Err(Error::InvalidName);
+        }
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            name,
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash,
+            String::from_str(&env, ""),
+        )?;
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a new session whose check-ins award `weight` times the configured
+    /// points (1 to 100, e.g. more for workshops than for socials).
+    pub fn set_hash_weighted(env: Env, admin: Address, new_hash: BytesN<32>, weight: u32) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        if !(1..=Self::MAX_SESSION_WEIGHT).contains(&weight) {
+            return Err(Error::InvalidWeight);
+        }
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            weight,
+            new_hash,
+            String::from_str(&env, ""),
+        )?;
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a new session that accepts at most `max_attendees` check-ins
+    /// (venue capacity). Zero means unlimited.
+    pub fn set_hash_with_capacity(env: Env, admin: Address, new_hash: BytesN<32>, max_attendees: u32) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+        )?;
+
+        let capacity_key = StorageKey::SessionCapacity(new_hash);
+        env.storage().persistent().set(&capacity_key, &max_attendees);
+
+        Self::bump_session(&env, &capacity_key);
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a new session that stops accepting check-ins after `expires_at`.
+    pub fn set_hash_with_expiry(env: Env, admin: Address, new_hash: BytesN<32>, expires_at: u32) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+        )?;
+
+        let expiry_key = StorageKey::SessionExpiry(new_hash);
+        env.storage().persistent().set(&expiry_key, &expires_at);
+
+        Self::bump_session(&env, &expiry_key);
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a new session with a short memo (location, topic; up to 64
+    /// bytes) that is stored in its metadata and carried by the `new_sess` event.
+    pub fn set_hash_with_memo(env: Env, admin: Address, new_hash: BytesN<32>, memo: String) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        if memo.len() > Self::MAX_SESSION_MEMO {
+            return Err(Error::InvalidMemo);
+        }
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            new_hash,
+            memo,
+        )?;
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Starts a session whose check-ins count towards the logical event
+    /// `event_id`. Rotating the code of the active event (e.g. a fresh QR every few minutes)
+    /// keeps everyone already checked in present and is allowed even in strict mode.
+    /// A plain session is its own event, so its hash can be used as `event_id` to rotate it.
+    pub fn set_hash_for_event(env: Env, admin: Address, new_hash: BytesN<32>, event_id: BytesN<32>) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash,
+            String::from_str(&env, ""),
+            Self::DEFAULT_SESSION_WEIGHT,
+            event_id,
+            String::from_str(&env, ""),
+        )?;
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Replaces the active session's code with `new_hash` as a rotation of
+    /// the same event, keeping its name, weight, memo, capacity and expiry. Presence is stored
+    /// per event, so everyone already checked in stays present without copying any entries.
+    pub fn rotate_hash(env: Env, admin: Address, new_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        let old_hash: BytesN<32> = env
         .storage()
         .persistent()
         .get(&StorageKey::ActiveHash)
         .ok_or(Error::NoActiveSession)?;
 
-        env.storage().persistent().extend_ttl(
-            &StorageKey::ActiveHash,
-            Self::TTL_THRESHOLD,
-            Self::TTL_BUMP_30D
-        );
+        let info = Self::read_session_info(&env, &StorageKey::SessionInfo(old_hash.clone()))
+        .ok_or(Error::NoActiveSession)?;
+        let expiry: Option<u32> = env.storage().persistent().get(&StorageKey::SessionExpiry(old_hash.clone()));
+        let capacity = Self::session_capacity(&env, &old_hash);
 
-        if submitted_hash != stored_hash {
-            return Err(Error::IncorrectHash);
+        Self::start_session(
+            &env,
+            &admin,
+            new_hash.clone(),
+            info.name,
+            info.weight,
+            info.event_id,
+            info.memo,
+        )?;
+
+        // Written after `start_session`, which resets both for the new hash
+        let expiry_key = StorageKey::SessionExpiry(new_hash.clone());
+        match expiry {
+            Some(expires_at) => {
+                env.storage().persistent().set(&expiry_key, &expires_at);
+
+                Self::bump_session(&env, &expiry_key);
+            }
+            None => env.storage().persistent().remove(&expiry_key),
         }
 
-        let presence_key = StorageKey::Presence(stored_hash.clone(), user.clone());
+        if capacity > 0 {
+            let capacity_key = StorageKey::SessionCapacity(new_hash);
+            env.storage().persistent().set(&capacity_key, &capacity);
 
-        if env.storage().persistent().has(&presence_key) {
-            return Err(Error::AlreadyRegistered);
+            Self::bump_session(&env, &capacity_key);
         }
 
-        env.storage().persistent().set(&presence_key, &true);
+        Ok(())
+    }
 
-        env.storage().persistent().extend_ttl(
-            &presence_key,
-            Self::TTL_THRESHOLD,
-            Self::TTL_BUMP_30D
-        );
+    /// (Admin or moderator) Pushes back the active session's expiry by `additional_ledgers`
+    /// without rotating its hash. Sessions without an expiry have nothing to extend
+    /// (`InvalidConfig`), and an expired session stays closed (`SessionExpired`).
+    pub fn extend_session(env: Env, admin: Address, additional_ledgers: u32) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
 
-        let profile_key = StorageKey::UserProfile(user.clone());
-        let nickname = if let Some(profile) = env.storage().persistent().get::<StorageKey, UserProfile>(&profile_key) {
-            env.storage().persistent().extend_ttl(
-                &profile_key,
-                Self::TTL_THRESHOLD,
-                Self::TTL_BUMP_90D
-            );
-            profile.nickname
-        } else {
-            String::from_str(&env, "")
-        };
+        let active_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
 
-        env.events().publish(
-            (symbol_short!("present"),),
-                             (user, stored_hash, nickname) // (user, session_hash, nickname)
-        );
+        if Self::is_expired(&env, &active_hash) {
+            return Err(Error::SessionExpired);
+        }
+
+        let expiry_key = StorageKey::SessionExpiry(active_hash.clone());
+        let expires_at: u32 = env.storage().persistent().get(&expiry_key).ok_or(Error::InvalidConfig)?;
+
+        if additional_ledgers == 0 {
+            return Err(Error::InvalidConfig);
+        }
+
+        let expires_at = expires_at.saturating_add(additional_ledgers);
+        env.storage().persistent().set(&expiry_key, &expires_at);
+
+        Self::bump_session(&env, &expiry_key);
+
+        Self::emit_extend(&env, admin, active_hash, expires_at);
 
         Ok(())
     }
 
-    /// (Admin only) Transfers admin rights to a new address.
-    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), Error> {
-        let current_admin: Address = env.storage().instance()
-        .get(&StorageKey::Admin)
-        .ok_or(Error::NotInitialized)?;
+    /// (Admin or moderator) Closes the active session so it stops accepting registrations.
+    pub fn close_session(env: Env, admin: Address) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
 
-        current_admin.require_auth();
-        new_admin.require_auth();
+        let closed_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
 
-        env.storage().instance().set(&StorageKey::Admin, &new_admin);
+        env.storage().persistent().remove(&StorageKey::ActiveHash);
+        env.storage().instance().remove(&StorageKey::ActiveHashLiveUntil);
 
-        env.storage().instance().extend_ttl(Self::TTL_THRESHOLD, Self::TTL_BUMP_30D);
+        let closed_key = StorageKey::ClosedSession(closed_hash.clone());
+        env.storage().persistent().set(&closed_key, &true);
 
-        env.events().publish(
-            (symbol_short!("adm_xfer"),),
-                             (current_admin, new_admin)
-        );
+        Self::bump_session(&env, &closed_key);
+
+        let total_attendees: u32 = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::AttendanceCount(Self::event_of(&env, &closed_hash)))
+        .unwrap_or(0);
+        let created_at = Self::read_session_info(&env, &StorageKey::SessionInfo(closed_hash.clone()))
+        .map(|info| info.created_at)
+        .unwrap_or(0);
+
+        Self::emit_close(&env, closed_hash.clone());
+        Self::emit_summary(&env, closed_hash, total_attendees, created_at, env.ledger().sequence());
 
         Ok(())
     }
 
-    /// (User function) Creates or updates a user's profile with a nickname.
-    pub fn set_profile(env: Env, user: Address, nickname: String) -> Result<(), Error> {
+    /// (User function) Registers the caller's presence for the active session.
+    pub fn register(env: Env, user: Address, submitted_hash: BytesN<32>) -> Result<(), Error> {
         user.require_auth();
 
-        if nickname.len() < 3 || nickname.len() > 32 {
-            return Err(Error::InvalidNickname);
-        }
+        Self::record_presence(&env, user, submitted_hash)
+    }
 
-        let profile = UserProfile {
-            nickname: nickname.clone(),
-            registered_at: env.ledger().sequence(),
-        };
+    /// (User function) Registers with a shared phrase instead of raw hash bytes: the active hash
+    /// must be the SHA-256 of the password's UTF-8 bytes, so the admin calls `set_hash` with it.
+    /// The phrase becomes public in the first check-in transaction, like a hash would.
+    pub fn register_with_password(env: Env, user: Address, password: String) -> Result<(), Error> {
+        user.require_auth();
 
-        let profile_key = StorageKey::UserProfile(user.clone());
-        env.storage().persistent().set(&profile_key, &profile);
+        let submitted_hash = BytesN::from(env.crypto().sha256(&password.to_bytes()));
 
-        env.storage().persistent().extend_ttl(
-            &profile_key,
-            Self::TTL_THRESHOLD,
-            Self::TTL_BUMP_90D
-        );
+        Self::record_presence(&env, user, submitted_hash)
+    }
 
-        env.events().publish(
-            (symbol_short!("profile"),),
-                             (user, nickname)
-        );
+    /// (Admin only) Sponsored check-in: an admin submits (and pays for) the transaction on
+    /// behalf of `user`. Both authorizations are required: `admin` authorizes as the submitter
+    /// and `user` through a pre-signed auth entry for this call.
+    pub fn register_for(env: Env, admin: Address, user: Address, submitted_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+        user.require_auth();
 
-        Ok(())
+        Self::record_presence(&env, user, submitted_hash)
     }
 
-    // --- (View functions) ---
+    /// (View function) Preflights a check-in: runs every validation `register` performs and
+    /// returns the error it would fail with, without requiring auth or writing state. A token
+    /// reward configured to revert on failure is only checked by the real check-in.
+    pub fn can_register(env: Env, user: Address, submitted_hash: BytesN<32>) -> Result<(), Error> {
+        Self::validate_checkin(&env, &user, &submitted_hash).map(|_| ())
+    }
 
-    /// (View function) Retrieves a user's profile (if it exists).
-    pub fn get_profile(env: Env, user: Address) -> Option<UserProfile> {
-        let profile_key = StorageKey::UserProfile(user);
+    /// (User function) Registers the caller and returns the resulting presence state,
+    /// saving clients the follow-up `check_presence` round trip.
+    pub fn register_and_check(env: Env, user: Address, submitted_hash: BytesN<32>) -> Result<bool, Error> {
+        user.require_auth();
 
-        if let Some(profile) = env.storage().persistent().get::<StorageKey, UserProfile>(&profile_key) {
-            env.storage().persistent().extend_ttl(
-                &profile_key,
-                Self::TTL_THRESHOLD,
-                Self::TTL_BUMP_90D
-            );
-            Some(profile)
-        } else {
-            None
+        Self::record_presence(&env, user.clone(), submitted_hash)?;
+
+        Self::check_presence(env, user)
+    }
+
+    /// (User function) Registers several users at once (at most 3), each authorizing its own entry.
+    /// Returns one result per entry so a single bad hash does not abort the batch.
+    pub fn register_batch(env: Env, admin_sponsored: Vec<(Address, BytesN<32>)>) -> Result<Vec<Result<(), Error>>, Error> {
+        if admin_sponsored.len() > Self::MAX_REGISTER_BATCH {
+            return Err(Error::LimitTooLarge);
         }
+
+        let mut results = Vec::new(&env);
+        for (user, submitted_hash) in admin_sponsored.iter() {
+            user.require_auth();
+            results.push_back(Self::record_presence(&env, user, submitted_hash));
+        }
+        Ok(results)
     }
 
-    /// (View function) Checks if a user is registered for the CURRENT active session.
-    pub fn check_presence(env: Env, user: Address) -> bool {
+    /// (User function) Commits to a later `register_signed` call without revealing the session hash.
+    /// The commitment is `sha256(active_hash || user.to_xdr() || nonce)`.
+    pub fn submit_commitment(env: Env, user: Address, commitment: BytesN<32>) -> Result<(), Error> {
+        user.require_auth();
 
-        let current_hash: BytesN<32> = match env.storage().persistent().get(&StorageKey::ActiveHash) {
-            Some(hash) => hash,
-            None => return false,
-        };
+        let commitment_key = StorageKey::Commitment(user);
+        env.storage().persistent().set(&commitment_key, &commitment);
 
-        env.storage().persistent().extend_ttl(
-            &StorageKey::ActiveHash,
-            Self::TTL_THRESHOLD,
-            Self::TTL_BUMP_30D
-        );
+        Self::bump_session(&env, &commitment_key);
 
-        let presence_key = StorageKey::Presence(current_hash, user);
+        Ok(())
+    }
 
-        let is_present = env.storage().persistent().get(&presence_key).unwrap_or(false);
+    /// (User function) Registers by revealing the nonce behind a previously submitted commitment.
+    /// The shared session hash never appears in the transaction, so it cannot be front-run.
+    pub fn register_signed(env: Env, user: Address, nonce: BytesN<32>) -> Result<(), Error> {
+        user.require_auth();
 
-        if is_present {
-            env.storage().persistent().extend_ttl(
-                &presence_key,
-                Self::TTL_THRESHOLD,
-                Self::TTL_BUMP_30D
-            );
+        let nonce_key = StorageKey::UsedNonce(nonce.clone());
+        if env.storage().persistent().has(&nonce_key) {
+            return Err(Error::NonceReused);
         }
 
-        is_present
-    }
+        let commitment_key = StorageKey::Commitment(user.clone());
+        let commitment: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&commitment_key)
+        .ok_or(Error::NoCommitment)?;
 
-    /// (View function) Returns the current admin address.
-    pub fn get_admin(env: Env) -> Result<Address, Error> {
-        env.storage().instance().extend_ttl(Self::TTL_THRESHOLD, Self::TTL_BUMP_30D);
+        let stored_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
 
-        env.storage().instance()
-        .get(&StorageKey::Admin)
-        .ok_or(Error::NotInitialized)
+        let mut preimage = Bytes::from(stored_hash.clone());
+        preimage.append(&user.clone().to_xdr(&env));
+        preimage.append(&Bytes::from(nonce));
+
+        if BytesN::from(env.crypto().sha256(&preimage)) != commitment {
+            return Err(Error::IncorrectHash);
+        }
+
+        Self::record_presence(&env, user, stored_hash)?;
+
+        env.storage().persistent().remove(&commitment_key);
+        env.storage().persistent().set(&nonce_key, &true);
+
+        Self::bump_session(&env, &nonce_key);
+
+        Ok(())
     }
 
-    /// (View function) Returns the current active session hash (if any).
-    pub fn get_session(env: Env) -> Option<BytesN<32>> {
-        if let Some(hash) = env.storage().persistent().get(&StorageKey::ActiveHash) {
-            env.storage().persistent().extend_ttl(
-                &StorageKey::ActiveHash,
-                Self::TTL_THRESHOLD,
-                Self::TTL_BUMP_30D
-            );
-            Some(hash)
-        } else {
-            None
+    /// (User function) Withdraws the caller's presence from the active session.
+    pub fn deregister(env: Env, user: Address) -> Result<(), Error> {
+        user.require_auth();
+
+        let stored_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
+
+        // Attendance is final once the session window has passed
+        if Self::is_expired(&env, &stored_hash) {
+            return Err(Error::SessionExpired);
         }
+
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &stored_hash), user.clone());
+
+        if !env.storage().persistent().has(&presence_key) {
+            return Err(Error::NotRegistered);
+        }
+
+        Self::clear_presence(&env, &stored_hash, &user);
+
+        Self::emit_dereg(&env, user, stored_hash);
+
+        Ok(())
     }
 
-    /// (View function) Check presence for multiple users at once.
-    pub fn check_batch(env: Env, users: Vec<Address>) -> Vec<bool> {
-        let current_hash: BytesN<32> = match Self::get_session(env.clone()) {
-            Some(hash) => hash,
-            None => return Vec::new(&env),
-        };
+    /// (Admin or moderator) Marks a user present for the active session on their behalf.
+    pub fn admin_mark_present(env: Env, admin: Address, user: Address) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
 
-        let mut results = Vec::new(&env);
-        for user in users.iter() {
-            let presence_key = StorageKey::Presence(current_hash.clone(), user);
+        let stored_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
 
-            let is_present = env.storage().persistent().get(&presence_key).unwrap_or(false);
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &stored_hash), user.clone());
 
-            if is_present {
-                env.storage().persistent().extend_ttl(
-                    &presence_key,
-                    Self::TTL_THRESHOLD,
-                    // --- ESTA ERA A LINHA COM O TYPO (AGORA CORRIGIDA) ---
-                    Self::TTL_BUMP_30D
-                );
-            }
-            results.push_back(is_present);
+        if env.storage().persistent().has(&presence_key) {
+            return Err(Error::AlreadyRegistered);
+        }
+
+        let (_, first_checkin) = Self::write_presence(&env, &stored_hash, &user, Some(admin.clone()))?;
+
+        let nickname = Self::nickname_of(&env, &user);
+
+        Self::emit_admin_mark(&env, admin, user.clone(), stored_hash.clone(), nickname);
+
+        if first_checkin {
+            Self::emit_first_checkin(&env, Self::event_identity(&env, &user), stored_hash);
         }
-        results
+
+        Ok(())
     }
-}
+
+    /// (Admin only) Seeds the active session with pre-registered attendees, skipping users
+    /// already present. Requires the admin's auth once for the whole batch (max 12 users).
+    /// Returns how many users were newly marked present.
+    pub fn admin_preregister(env: Env, admin: Address, users: Vec<Address>) -> Result<u32, Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if users.len() > Self::MAX_PREREGISTER {
+            return Err(Error::LimitTooLarge);
+        }
+
+        let stored_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
+
+        let event_id = Self::event_of(&env, &stored_hash);
+
+        let mut added = 0;
+        for user in users.iter() {
+            let presence_key = StorageKey::Presence(event_id.clone(), user.clone());
+
+            if env.storage().persistent().has(&presence_key) {
+                continue;
+            }
+
+            let (_, first_checkin) = Self::write_presence(&env, &stored_hash, &user, Some(admin.clone()))?;
+            let nickname = Self::nickname_of(&env, &user);
+            Self::emit_admin_mark(&env, admin.clone(), user.clone(), stored_hash.clone(), nickname);
+            if first_checkin {
+                Self::emit_first_checkin(&env, Self::event_identity(&env, &user), stored_hash.clone());
+            }
+            added += 1;
+        }
+
+        Ok(added)
+    }
+
+    /// (Admin only) Clears a user's presence for any session, active or historical.
+    pub fn admin_clear_presence(env: Env, admin: Address, user: Address, session_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &session_hash), user.clone());
+
+        if !env.storage().persistent().has(&presence_key) {
+            return Err(Error::NotRegistered);
+        }
+
+        Self::clear_presence(&env, &session_hash, &user);
+
+        let nickname = Self::nickname_of(&env, &user);
+
+        Self::emit_admin_clear(&env, admin, user, session_hash, nickname);
+
+        Ok(())
+    }
+
+    /// (Admin only) Queues a past session's event for `purge_evicted`, which deletes its attendee
+    /// list and every presence entry in bounded chunks. The active event cannot be purged, and a
+    /// full queue fails with `LimitTooLarge`. Attendance counts, check-in totals and session
+    /// metadata are kept.
+    pub fn admin_purge_session(env: Env, admin: Address, session_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let event_id = Self::event_of(&env, &session_hash);
+
+        let active_hash: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+        if active_hash.map(|hash| Self::event_of(&env, &hash)) == Some(event_id.clone()) {
+            return Err(Error::CannotPurgeActive);
+        }
+
+        let queue_key = StorageKeyExt::PurgeQueue;
+        let mut queue: Vec<BytesN<32>> = env.storage().persistent().get(&queue_key).unwrap_or(Vec::new(&env));
+        if !Self::queue_purge(&mut queue, event_id) {
+            return Err(Error::LimitTooLarge);
+        }
+
+        env.storage().persistent().set(&queue_key, &queue);
+
+        Self::bump_session(&env, &queue_key);
+
+        Self::emit_admin_purge(&env, admin, session_hash);
+
+        Ok(())
+    }
+
+    /// (Admin only) Purges the attendance data of evicted sessions and of `admin_purge_session`,
+    /// oldest event first, covering at most `max_attendees` attendees (capped at 10) so each call
+    /// stays within the write limits. Events that became active again are dropped from the queue
+    /// untouched; they and events without attendees count as one attendee each. Returns how many
+    /// presences were removed; call it again while `purge_queue_len` is non-zero.
+    pub fn purge_evicted(env: Env, admin: Address, max_attendees: u32) -> Result<u32, Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let queue_key = StorageKeyExt::PurgeQueue;
+        let mut queue: Vec<BytesN<32>> = env.storage().persistent().get(&queue_key).unwrap_or(Vec::new(&env));
+        let active_event: Option<BytesN<32>> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .map(|hash| Self::event_of(&env, &hash));
+
+        let mut budget = max_attendees.min(Self::MAX_PURGE_CHUNK);
+        let mut removed: u32 = 0;
+        while budget > 0 {
+            let Some(event_id) = queue.first() else {
+                break;
+            };
+
+            // Entries without attendees to purge still cost one unit, keeping the reads bounded too
+            if active_event.as_ref() == Some(&event_id) {
+                queue.pop_front_unchecked();
+                budget -= 1;
+                continue;
+            }
+
+            let attendees_key = StorageKey::Attendees(event_id.clone());
+            let mut attendees: Vec<Address> = env.storage().persistent().get(&attendees_key).unwrap_or(Vec::new(&env));
+            if attendees.is_empty() {
+                budget -= 1;
+            }
+            while budget > 0 {
+                let Some(user) = attendees.pop_back() else {
+                    break;
+                };
+                if Self::purge_attendee(&env, &event_id, user) {
+                    removed += 1;
+                }
+                budget -= 1;
+            }
+
+            if attendees.is_empty() {
+                env.storage().persistent().remove(&attendees_key);
+                queue.pop_front_unchecked();
+                Self::emit_purged(&env, event_id);
+            } else {
+                env.storage().persistent().set(&attendees_key, &attendees);
+            }
+        }
+
+        env.storage().persistent().set(&queue_key, &queue);
+
+        Self::bump_session(&env, &queue_key);
+
+        Ok(removed)
+    }
+
+    /// Returns how many queued events still await `purge_evicted`.
+    pub fn purge_queue_len(env: Env) -> u32 {
+        env.storage()
+        .persistent()
+        .get::<StorageKeyExt, Vec<BytesN<32>>>(&StorageKeyExt::PurgeQueue)
+        .map(|queue| queue.len())
+        .unwrap_or(0)
+    }
+
+    /// (Admin only) Resets a session's event, e.g. after a test run: withdraws the check-ins of up
+    /// to `max_attendees` attendees (capped at 10) like `deregister` would and returns how many
+    /// presences were cleared. Call it again while `get_attendance_count` is non-zero; the last
+    /// chunk zeroes the count. Session metadata is kept. The active event is refused unless `force` is set.
+    pub fn admin_clear_session(
+        env: Env,
+        admin: Address,
+        session_hash: BytesN<32>,
+        force: bool,
+        max_attendees: u32,
+    ) -> Result<u32, Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let event_id = Self::event_of(&env, &session_hash);
+
+        let active_hash: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+        if !force && active_hash.map(|hash| Self::event_of(&env, &hash)) == Some(event_id.clone()) {
+            return Err(Error::CannotPurgeActive);
+        }
+
+        let attendees_key = StorageKey::Attendees(event_id.clone());
+        let mut attendees: Vec<Address> = env.storage().persistent().get(&attendees_key).unwrap_or(Vec::new(&env));
+
+        // About 4 writes per attendee, so each call stays within the per-transaction limits
+        let mut budget = max_attendees.min(Self::MAX_PURGE_CHUNK);
+        let mut cleared: u32 = 0;
+        while budget > 0 {
+            let Some(user) = attendees.pop_back() else {
+                break;
+            };
+            budget -= 1;
+
+            env.storage().persistent().remove(&StorageKey::PresenceNickname(event_id.clone(), user.clone()));
+            env.storage().persistent().remove(&StorageKeyExt::Receipt(event_id.clone(), user.clone()));
+
+            let presence_key = StorageKey::Presence(event_id.clone(), user.clone());
+            if !env.storage().persistent().has(&presence_key) {
+                continue;
+            }
+            env.storage().persistent().remove(&presence_key);
+            cleared += 1;
+
+            let total_key = StorageKey::TotalCheckins(user);
+            if let Some(total) = env.storage().persistent().get::<StorageKey, u32>(&total_key) {
+                env.storage().persistent().set(&total_key, &total.saturating_sub(1));
+            }
+        }
+
+        let count_key = StorageKey::AttendanceCount(event_id);
+        let remaining = attendees.len();
+        if remaining == 0 {
+            env.storage().persistent().remove(&attendees_key);
+            env.storage().persistent().set(&count_key, &0u32);
+        } else {
+            env.storage().persistent().set(&attendees_key, &attendees);
+            let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+            env.storage().persistent().set(&count_key, &count.saturating_sub(cleared));
+        }
+
+        Self::bump_session(&env, &count_key);
+
+        let checkins_all: u32 = env.storage().instance().get(&StorageKeyExt::TotalCheckinsAll).unwrap_or(0);
+        env.storage().instance().set(&StorageKeyExt::TotalCheckinsAll, &checkins_all.saturating_sub(cleared));
+
+        Self::emit_admin_clear_session(&env, admin, session_hash, cleared, remaining);
+
+        Ok(cleared)
+    }
+
+    /// (Admin only) Hides a session created by mistake from `list_sessions` without touching
+    /// its attendance: presence, counts and metadata stay queryable.
+    pub fn archive_session(env: Env, admin: Address, session_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if !env.storage().persistent().has(&StorageKey::SessionInfo(session_hash.clone())) {
+            return Err(Error::IncorrectHash);
+        }
+
+        let archived_key = StorageKey::Archived(session_hash.clone());
+        env.storage().persistent().set(&archived_key, &true);
+
+        Self::bump_session(&env, &archived_key);
+
+        Self::emit_archive(&env, admin, session_hash);
+
+        Ok(())
+    }
+
+    /// (Admin or moderator) Overrides a user's nickname, e.g. to remove an abusive one, creating
+    /// a profile if they have none. The nickname is validated like in `set_profile`. The user's
+    /// update cooldown restarts, so the old name cannot be restored right away.
+    pub fn admin_set_nickname(env: Env, admin: Address, user: Address, nickname: String) -> Result<(), Error> {
+        Self::require_moderator_or_admin(&env, &admin)?;
+
+        Self::override_nickname(&env, &admin, &user, &nickname)
+    }
+
+    /// (Admin only) Seeds profiles for an onboarding cohort with one admin authorization
+    /// (at most 15 entries). Each entry is handled like `admin_set_nickname`; the returned
+    /// vector holds one result per entry, so rejected nicknames do not abort the batch.
+    pub fn admin_set_profiles(env: Env, admin: Address, entries: Vec<(Address, String)>) -> Result<Vec<Result<(), Error>>, Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if entries.len() > Self::MAX_PROFILE_BATCH {
+            return Err(Error::LimitTooLarge);
+        }
+
+        let mut results = Vec::new(&env);
+        for (user, nickname) in entries.iter() {
+            results.push_back(Self::override_nickname(&env, &admin, &user, &nickname));
+        }
+        Ok(results)
+    }
+
+    /// (Admin only) Replaces the contract code with an already uploaded WASM, keeping all storage.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+        Self::emit_upgrade(&env, admin, new_wasm_hash);
+
+        Ok(())
+    }
+
+    /// (Admin only) Transfers the primary admin rights to a new address.
+    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        let current_admin = Self::require_initialized(&env)?;
+
+        current_admin.require_auth();
+        new_admin.require_auth();
+
+        Self::replace_primary_admin(&env, &new_admin)?;
+
+        Self::emit_admin_transfer(&env, current_admin, new_admin);
+
+        Ok(())
+    }
+
+    /// (View function) Returns the topic prefix set at initialization, if any.
+    pub fn event_prefix(env: Env) -> Option<Symbol> {
+        env.storage().instance().get(&StorageKeyExt::EventPrefix)
+    }
+
+    /// (View function) Returns the primary admins in the order they took over, starting with
+    /// the one set by `initialize` (the 50 most recent are kept).
+    pub fn admin_history(env: Env) -> Vec<Address> {
+        env.storage().instance().get(&StorageKeyExt::AdminHistory).unwrap_or(Vec::new(&env))
+    }
+
+    /// (Admin only) First step of a two-step transfer: the primary admin nominates
+    /// `new_admin`, who then has to call `accept_admin`. Replaces any earlier proposal.
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        let current_admin = Self::require_initialized(&env)?;
+
+        current_admin.require_auth();
+
+        env.storage().instance().set(&StorageKey::PendingAdmin, &new_admin);
+
+        Self::bump_instance(&env);
+
+        Self::emit_admin_proposed(&env, current_admin, new_admin);
+
+        Ok(())
+    }
+
+    /// (User function) Second step of a two-step transfer: the proposed admin accepts
+    /// and becomes the primary admin.
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        let current_admin = Self::require_initialized(&env)?;
+
+        let new_admin: Address = env
+        .storage()
+        .instance()
+        .get(&StorageKey::PendingAdmin)
+        .ok_or(Error::NoPendingAdmin)?;
+
+        new_admin.require_auth();
+
+        Self::replace_primary_admin(&env, &new_admin)?;
+
+        Self::emit_admin_accepted(&env, current_admin, new_admin);
+
+        Ok(())
+    }
+
+    /// (Admin only) Withdraws a pending admin proposal.
+    pub fn cancel_admin_transfer(env: Env) -> Result<(), Error> {
+        let current_admin = Self::require_initialized(&env)?;
+
+        current_admin.require_auth();
+
+        if !env.storage().instance().has(&StorageKey::PendingAdmin) {
+            return Err(Error::NoPendingAdmin);
+        }
+
+        env.storage().instance().remove(&StorageKey::PendingAdmin);
+
+        Self::bump_instance(&env);
+
+        Ok(())
+    }
+
+    /// (Admin only) Grants admin rights to `new_admin`. Any existing admin may add others.
+    pub fn add_admin(env: Env, admin: Address, new_admin: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut admins = Self::load_admins(&env)?;
+        if !admins.contains(&new_admin) {
+            admins.push_back(new_admin.clone());
+            Self::save_admins(&env, &admins);
+        }
+
+        Self::emit_admin_added(&env, admin, new_admin);
+
+        Ok(())
+    }
+
+    /// (Admin only) Revokes admin rights from `removed`. The last admin cannot be removed.
+    /// Removing the primary admin promotes the next one, which is recorded in `admin_history`.
+    pub fn remove_admin(env: Env, admin: Address, removed: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut admins = Self::load_admins(&env)?;
+        let index = admins.first_index_of(&removed).ok_or(Error::NotAdmin)?;
+
+        if admins.len() == 1 {
+            return Err(Error::CannotRemoveLastAdmin);
+        }
+
+        admins.remove(index);
+        Self::save_admins(&env, &admins);
+
+        if index == 0 {
+            // Contracts initialized before the history existed start it with their current primary
+            if !env.storage().instance().has(&StorageKeyExt::AdminHistory) {
+                Self::push_admin_history(&env, &removed);
+            }
+            Self::push_admin_history(&env, &admins.get_unchecked(0));
+        }
+
+        Self::emit_admin_removed(&env, admin, removed);
+
+        Ok(())
+    }
+
+    /// (Admin only) Grants the moderator role, which may run sessions (`set_hash*`,
+    /// `close_session`, `admin_mark_present`) but not manage roles, settings or upgrades.
+    pub fn add_moderator(env: Env, admin: Address, moderator: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut moderators = Self::load_moderators(&env);
+        if !moderators.contains(&moderator) {
+            moderators.push_back(moderator.clone());
+            env.storage().instance().set(&StorageKey::Moderators, &moderators);
+        }
+
+        Self::bump_instance(&env);
+
+        Self::emit_moderator_added(&env, admin, moderator);
+
+        Ok(())
+    }
+
+    /// (Admin only) Revokes the moderator role.
+    pub fn remove_moderator(env: Env, admin: Address, moderator: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut moderators = Self::load_moderators(&env);
+        let index = moderators.first_index_of(&moderator).ok_or(Error::NotModerator)?;
+
+        moderators.remove(index);
+        env.storage().instance().set(&StorageKey::Moderators, &moderators);
+
+        Self::bump_instance(&env);
+
+        Self::emit_moderator_removed(&env, admin, moderator);
+
+        Ok(())
+    }
+
+    /// (Admin only) Pauses or resumes registrations and profile updates.
+    pub fn set_paused(env: Env, admin: Address, paused: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::Paused, &paused);
+
+        Self::bump_instance(&env);
+
+        Self::emit_paused(&env, admin.clone(), paused);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("paused"), paused.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets the accepted nickname length range (both within 1..=64).
+    pub fn set_nickname_bounds(env: Env, admin: Address, min: u32, max: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if min < 1 || max > Self::NICKNAME_LIMIT || min > max {
+            return Err(Error::InvalidConfig);
+        }
+
+        env.storage().instance().set(&StorageKey::NicknameBounds, &(min, max));
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("nick_bnds"), (min, max).into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets how many points each first-time check-in awards.
+    pub fn set_points_per_checkin(env: Env, admin: Address, amount: u64) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::PointsPerCheckin, &amount);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("points"), amount.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets the bonus paid by `claim_session_reward`.
+    pub fn set_session_reward(env: Env, admin: Address, amount: u64) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::SessionReward, &amount);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("reward"), amount.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Makes inactive users lose `rate_percent` of their points for every `period`
+    /// ledgers without a check-in (applied through `apply_decay`). A zero rate disables decay.
+    pub fn set_decay_rate(env: Env, admin: Address, rate_percent: u32, period: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if rate_percent > 100 || (rate_percent > 0 && period == 0) {
+            return Err(Error::InvalidConfig);
+        }
+
+        let config = DecayConfig { rate_percent, period };
+        env.storage().instance().set(&StorageKey::DecayConfig, &config);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("decay"), config.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Pays `amount` of `token` from the contract's own balance on every
+    /// first-time check-in. If a payout fails (e.g. the contract ran dry) presence is still
+    /// recorded and `reward_fail` is emitted, unless `revert_on_failure` is set, in which
+    /// case the check-in fails with `RewardTransferFailed`. An amount of 0 disables payouts.
+    pub fn set_reward_token(
+        env: Env,
+        admin: Address,
+        token: Address,
+        amount: i128,
+        revert_on_failure: bool,
+    ) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if amount < 0 {
+            return Err(Error::InvalidConfig);
+        }
+
+        let config = RewardConfig {
+            token,
+            amount,
+            revert_on_failure,
+        };
+        env.storage().instance().set(&StorageKey::RewardToken, &config);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("rwd_token"), config.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Transfers `amount` of the configured reward token from the contract's
+    /// balance to `to`, so unused funds are never locked in. Payouts keep their configuration.
+    pub fn withdraw_rewards(env: Env, admin: Address, to: Address, amount: i128) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let config: RewardConfig = env
+        .storage()
+        .instance()
+        .get(&StorageKey::RewardToken)
+        .ok_or(Error::NoRewardToken)?;
+
+        if amount <= 0 {
+            return Err(Error::InvalidConfig);
+        }
+
+        let client = token::TokenClient::new(&env, &config.token);
+        let paid = matches!(
+            client.try_transfer(&env.current_contract_address(), &to, &amount),
+            Ok(Ok(()))
+        );
+
+        if !paid {
+            return Err(Error::RewardTransferFailed);
+        }
+
+        Self::emit_withdraw(&env, admin, to, config.token, amount);
+
+        Ok(())
+    }
+
+    /// (View function) Returns the token reward configuration (if any).
+    pub fn get_reward_token(env: Env) -> Option<RewardConfig> {
+        env.storage().instance().get(&StorageKey::RewardToken)
+    }
+
+    /// (Admin only) Sets the NFT contract whose `mint(to: Address, session_hash: BytesN<32>)` is
+    /// called on every first-time check-in to issue a proof-of-attendance badge.
+    pub fn set_badge_contract(env: Env, admin: Address, nft: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::BadgeContract, &nft);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("badge"), nft.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Overrides the TTL threshold and bump values used for all storage.
+    /// `session_bump` applies to instance and session data, `profile_bump` to user data.
+    pub fn set_ttl_config(env: Env, admin: Address, threshold: u32, session_bump: u32, profile_bump: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if threshold >= session_bump || threshold >= profile_bump {
+            return Err(Error::InvalidConfig);
+        }
+
+        let config = TtlConfig {
+            threshold,
+            session_bump,
+            profile_bump,
+        };
+        env.storage().instance().set(&StorageKey::TtlConfig, &config);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("ttl"), config.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (View function) Returns the TTL values currently in effect.
+    pub fn get_ttl_config(env: Env) -> TtlConfig {
+        Self::ttl_config(&env)
+    }
+
+    /// (Admin only) Sets whether admins may check into sessions (allowed by default).
+    pub fn set_admin_can_attend(env: Env, admin: Address, allowed: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::AdminCanAttend, &allowed);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("adm_att"), allowed.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets whether users need a profile before checking in (off by default).
+    pub fn set_require_profile(env: Env, admin: Address, required: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::RequireProfile, &required);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("req_prof"), required.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets whether new profiles need at least one prior check-in (off by default),
+    /// to keep spam accounts out. Existing profiles can still be updated. Combined with
+    /// `set_require_profile`, newcomers can only get in through admin marks.
+    pub fn set_profile_requires_attendance(env: Env, admin: Address, required: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::ProfileRequiresAttendance, &required);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("prof_att"), required.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Enables strict sessions: starting a session fails with `SessionStillActive`
+    /// while another one is active, so attendees are never stranded by an accidental overwrite.
+    pub fn set_strict_sessions(env: Env, admin: Address, strict: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::StrictSessions, &strict);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("strict"), strict.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets how many session hashes `list_sessions` retains (1 to 100, default 100).
+    /// Starting a session beyond the limit evicts the oldest ones (at most 5 per call) and queues
+    /// their events for `purge_evicted`; lowering the limit drains over the following sessions.
+    pub fn set_max_sessions_retained(env: Env, admin: Address, max_sessions: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if !(1..=Self::MAX_SESSIONS).contains(&max_sessions) {
+            return Err(Error::InvalidConfig);
+        }
+
+        env.storage().instance().set(&StorageKey::MaxSessionsRetained, &max_sessions);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("max_sess"), max_sessions.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets how many ledgers new sessions stay open by default. Zero means
+    /// sessions never expire unless started with `set_hash_with_expiry`.
+    pub fn set_default_session_duration(env: Env, admin: Address, ledgers: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::DefaultSessionDuration, &ledgers);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("sess_dur"), ledgers.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets how many ledgers a user must wait between two check-ins, so one device
+    /// cannot hop through rapidly rotating sessions. Zero (the default) disables the cooldown.
+    pub fn set_checkin_cooldown(env: Env, admin: Address, ledgers: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::CheckinCooldown, &ledgers);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("chk_cd"), ledgers.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Sets how many ledgers a user must wait between profile updates.
+    /// Zero disables the cooldown.
+    pub fn set_profile_cooldown(env: Env, admin: Address, ledgers: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::ProfileCooldown, &ledgers);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("prof_cd"), ledgers.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (Admin only) Freezes `set_profile`, `set_bio` and `delete_profile`, e.g. so display
+    /// names cannot change mid-competition. Check-ins and admin overrides keep working.
+    pub fn set_profiles_locked(env: Env, admin: Address, locked: bool) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKeyExt::ProfilesLocked, &locked);
+
+        Self::bump_instance(&env);
+
+        Self::emit_config_changed(&env, admin, symbol_short!("prof_lock"), locked.into_val(&env));
+
+        Ok(())
+    }
+
+    /// (User function) Opts the user in or out of identifying check-in events. Opted-out
+    /// check-ins still count, but `present`, `first_in`, `reward_fail` and `badge_fail` carry an
+    /// all-zero placeholder address instead of the user's, and `present` an empty nickname and
+    /// an all-zero receipt.
+    pub fn set_opt_out(env: Env, user: Address, opted_out: bool) -> Result<(), Error> {
+        Self::require_initialized(&env)?;
+
+        user.require_auth();
+
+        let opt_out_key = StorageKey::OptedOut(user);
+        if opted_out {
+            env.storage().persistent().set(&opt_out_key, &true);
+
+            Self::bump_profile(&env, &opt_out_key);
+        } else {
+            env.storage().persistent().remove(&opt_out_key);
+        }
+
+        Ok(())
+    }
+
+    /// (View function) Returns whether the user opted out of identifying check-in events.
+    pub fn is_opted_out(env: Env, user: Address) -> bool {
+        env.storage().persistent().has(&StorageKey::OptedOut(user))
+    }
+
+    /// (User function) Creates or updates a user's profile with a nickname, an optional
+    /// avatar / metadata URI and an optional bio of up to 160 bytes (empty strings for none).
+    pub fn set_profile(env: Env, user: Address, nickname: String, avatar_uri: String, bio: String) -> Result<(), Error> {
+        user.require_auth();
+
+        Self::require_initialized(&env)?;
+
+        Self::require_not_paused(&env)?;
+
+        Self::require_profiles_unlocked(&env)?;
+
+        Self::validate_nickname(&env, &nickname)?;
+
+        if avatar_uri.len() > Self::MAX_AVATAR_URI {
+            return Err(Error::InvalidAvatarUri);
+        }
+
+        if bio.len() > Self::MAX_BIO {
+            return Err(Error::InvalidBio);
+        }
+
+        let now = env.ledger().sequence();
+        let profile_key = StorageKey::UserProfile(user.clone());
+        let existing = Self::read_profile(&env, &profile_key);
+
+        if let Some(previous) = &existing {
+            Self::require_profile_cooldown(&env, previous)?;
+        } else {
+            let requires_attendance: bool = env
+            .storage()
+            .instance()
+            .get(&StorageKey::ProfileRequiresAttendance)
+            .unwrap_or(false);
+            let total_checkins: u32 = env.storage().persistent().get(&StorageKey::TotalCheckins(user.clone())).unwrap_or(0);
+
+            if requires_attendance && total_checkins == 0 {
+                return Err(Error::MustAttendFirst);
+            }
+        }
+
+        Self::claim_nickname(&env, &user, &nickname)?;
+
+        let profile = UserProfile {
+            nickname: nickname.clone(),
+            registered_at: existing.as_ref().map(|previous| previous.registered_at).unwrap_or(now),
+            avatar_uri: avatar_uri.clone(),
+            last_updated: now,
+            registered_timestamp: existing
+            .map(|previous| previous.registered_timestamp)
+            .unwrap_or(env.ledger().timestamp()),
+            bio,
+        };
+
+        env.storage().persistent().set(&profile_key, &profile);
+
+        Self::bump_profile(&env, &profile_key);
+
+        Self::emit_profile(&env, user, profile);
+
+        Ok(())
+    }
+
+    /// (User function) Updates only the bio of an existing profile (up to 160 bytes, empty to
+    /// clear it). Counts as a profile update for the cooldown.
+    pub fn set_bio(env: Env, user: Address, bio: String) -> Result<(), Error> {
+        user.require_auth();
+
+        Self::require_initialized(&env)?;
+
+        Self::require_not_paused(&env)?;
+
+        Self::require_profiles_unlocked(&env)?;
+
+        if bio.len() > Self::MAX_BIO {
+            return Err(Error::InvalidBio);
+        }
+
+        let profile_key = StorageKey::UserProfile(user.clone());
+        let previous = Self::read_profile(&env, &profile_key).ok_or(Error::ProfileNotFound)?;
+
+        Self::require_profile_cooldown(&env, &previous)?;
+
+        let profile = UserProfile {
+            bio,
+            last_updated: env.ledger().sequence(),
+            ..previous
+        };
+
+        env.storage().persistent().set(&profile_key, &profile);
+
+        Self::bump_profile(&env, &profile_key);
+
+        Self::emit_profile(&env, user, profile);
+
+        Ok(())
+    }
+
+    /// (User function) Claims the one-time bonus for a session the user attended: the active
+    /// session when `session_hash` is `None`, or any earlier (e.g. closed) session otherwise.
+    /// Returns the user's new point total.
+    pub fn claim_session_reward(env: Env, user: Address, session_hash: Option<BytesN<32>>) -> Result<u64, Error> {
+        user.require_auth();
+
+        Self::require_not_paused(&env)?;
+
+        let session_hash = match session_hash {
+            Some(hash) => hash,
+            None => env
+            .storage()
+            .persistent()
+            .get(&StorageKey::ActiveHash)
+            .ok_or(Error::NoActiveSession)?,
+        };
+
+        let event_id = Self::event_of(&env, &session_hash);
+
+        if !env.storage().persistent().has(&StorageKey::Presence(event_id.clone(), user.clone())) {
+            return Err(Error::NotRegistered);
+        }
+
+        let claimed_key = StorageKey::Claimed(event_id, user.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            return Err(Error::AlreadyClaimed);
+        }
+
+        env.storage().persistent().set(&claimed_key, &true);
+
+        Self::bump_session(&env, &claimed_key);
+
+        let amount: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::SessionReward)
+        .unwrap_or(Self::DEFAULT_SESSION_REWARD);
+        let total = Self::add_points(&env, &user, amount);
+
+        Self::emit_reward_claimed(&env, user, session_hash, amount, total);
+
+        Ok(total)
+    }
+
+    /// (Anyone) Applies the configured decay to an inactive user's points for every full period
+    /// elapsed since their last check-in or the previous decay, at most 100 periods per call
+    /// (call again for the rest). Calling it again within a period changes nothing. Users
+    /// without a recorded check-in are left untouched. Returns the user's point total.
+    pub fn apply_decay(env: Env, user: Address) -> u64 {
+        let points_key = StorageKey::Points(user.clone());
+        let points: u64 = env.storage().persistent().get(&points_key).unwrap_or(0);
+
+        let config = match env.storage().instance().get::<StorageKey, DecayConfig>(&StorageKey::DecayConfig) {
+            Some(config) if config.rate_percent > 0 => config,
+            _ => return points,
+        };
+
+        let last_checkin: u32 = match env.storage().persistent().get(&StorageKey::LastCheckin(user.clone())) {
+            Some(ledger) => ledger,
+            None => return points,
+        };
+
+        let decay_key = StorageKey::LastDecay(user.clone());
+        let last_decay: u32 = env.storage().persistent().get(&decay_key).unwrap_or(0);
+        let anchor = last_checkin.max(last_decay);
+
+        let periods = (env.ledger().sequence().saturating_sub(anchor) / config.period).min(Self::MAX_DECAY_PERIODS);
+        if periods == 0 {
+            return points;
+        }
+
+        let mut remaining = points;
+        for _ in 0..periods {
+            remaining -= (remaining as u128 * config.rate_percent as u128 / 100) as u64;
+        }
+
+        env.storage().persistent().set(&points_key, &remaining);
+        // Only whole periods are consumed, so the partial one still counts next time
+        env.storage().persistent().set(&decay_key, &(anchor + periods * config.period));
+
+        Self::bump_profile(&env, &points_key);
+        Self::bump_profile(&env, &decay_key);
+
+        Self::emit_decay(&env, user, points - remaining, remaining);
+
+        remaining
+    }
+
+    /// (User function) Moves a profile to a new address after a key rotation, together with the
+    /// check-in total, points, streak, cooldown, decay and opt-out state. Both addresses must
+    /// authorize. Points and check-ins the new address already earned are added up; per-session
+    /// presences stay with `old`, so migrating is refused with `CheckedInActiveEvent` while `old`
+    /// has checked in to the active event (even if withdrawn), keeping `new` from earning it again.
+    pub fn migrate_profile(env: Env, old: Address, new: Address) -> Result<(), Error> {
+        old.require_auth();
+        new.require_auth();
+
+        Self::require_initialized(&env)?;
+
+        Self::require_not_paused(&env)?;
+
+        let old_profile_key = StorageKey::UserProfile(old.clone());
+        let profile = Self::read_profile(&env, &old_profile_key).ok_or(Error::ProfileNotFound)?;
+
+        let new_profile_key = StorageKey::UserProfile(new.clone());
+        if env.storage().persistent().has(&new_profile_key) {
+            return Err(Error::ProfileAlreadyExists);
+        }
+
+        if let Some(active_hash) = env.storage().persistent().get::<StorageKey, BytesN<32>>(&StorageKey::ActiveHash) {
+            let event_id = Self::event_of(&env, &active_hash);
+            if env.storage().persistent().has(&StorageKey::Presence(event_id.clone(), old.clone()))
+                || env.storage().persistent().has(&StorageKeyExt::Rewarded(event_id, old.clone()))
+            {
+                return Err(Error::CheckedInActiveEvent);
+            }
+        }
+
+        env.storage().persistent().remove(&old_profile_key);
+        env.storage().persistent().set(&new_profile_key, &profile);
+
+        Self::bump_profile(&env, &new_profile_key);
+
+        let owner_key = StorageKey::NicknameOwner(profile.nickname);
+        env.storage().persistent().set(&owner_key, &new);
+
+        Self::bump_profile(&env, &owner_key);
+
+        let old_total_key = StorageKey::TotalCheckins(old.clone());
+        if let Some(total) = env.storage().persistent().get::<StorageKey, u32>(&old_total_key) {
+            let new_total_key = StorageKey::TotalCheckins(new.clone());
+            let existing: Option<u32> = env.storage().persistent().get(&new_total_key);
+            env.storage().persistent().set(&new_total_key, &existing.unwrap_or(0).saturating_add(total));
+            env.storage().persistent().remove(&old_total_key);
+
+            // Both addresses were counted, but they now belong to one user
+            if existing.is_some() {
+                let unique_users: u32 = env.storage().instance().get(&StorageKey::UniqueUsers).unwrap_or(0);
+                env.storage().instance().set(&StorageKey::UniqueUsers, &unique_users.saturating_sub(1));
+            }
+
+            Self::bump_profile(&env, &new_total_key);
+        }
+
+        let old_points_key = StorageKey::Points(old.clone());
+        if let Some(points) = env.storage().persistent().get::<StorageKey, u64>(&old_points_key) {
+            env.storage().persistent().remove(&old_points_key);
+
+            // Free the leaderboard slot first so the new address can take it
+            let mut participants: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Participants)
+            .unwrap_or(Vec::new(&env));
+            if let Some(index) = participants.first_index_of(&old) {
+                participants.remove(index);
+                env.storage().persistent().set(&StorageKey::Participants, &participants);
+            }
+
+            Self::add_points(&env, &new, points);
+        }
+
+        // The streak only makes sense together with the last attended session index
+        for (old_key, new_key) in [
+            (StorageKey::Streak(old.clone()), StorageKey::Streak(new.clone())),
+            (StorageKey::LastSessionIndex(old.clone()), StorageKey::LastSessionIndex(new.clone())),
+        ] {
+            if let Some(value) = env.storage().persistent().get::<StorageKey, u32>(&old_key) {
+                env.storage().persistent().set(&new_key, &value);
+                env.storage().persistent().remove(&old_key);
+
+                Self::bump_profile(&env, &new_key);
+            }
+        }
+
+        // The later ledger wins, so neither the check-in cooldown nor the decay can be reset
+        for (old_key, new_key) in [
+            (StorageKey::LastCheckin(old.clone()), StorageKey::LastCheckin(new.clone())),
+            (StorageKey::LastDecay(old.clone()), StorageKey::LastDecay(new.clone())),
+        ] {
+            if let Some(value) = env.storage().persistent().get::<StorageKey, u32>(&old_key) {
+                let existing: u32 = env.storage().persistent().get(&new_key).unwrap_or(0);
+                env.storage().persistent().set(&new_key, &value.max(existing));
+                env.storage().persistent().remove(&old_key);
+
+                Self::bump_profile(&env, &new_key);
+            }
+        }
+
+        let old_opt_out_key = StorageKey::OptedOut(old.clone());
+        if env.storage().persistent().has(&old_opt_out_key) {
+            let new_opt_out_key = StorageKey::OptedOut(new.clone());
+            env.storage().persistent().set(&new_opt_out_key, &true);
+            env.storage().persistent().remove(&old_opt_out_key);
+
+            Self::bump_profile(&env, &new_opt_out_key);
+        }
+
+        Self::emit_profile_migrated(&env, old, new);
+
+        Ok(())
+    }
+
+    /// (User function) Deletes the caller's profile and releases its nickname.
+    pub fn delete_profile(env: Env, user: Address) -> Result<(), Error> {
+        user.require_auth();
+
+        Self::require_profiles_unlocked(&env)?;
+
+        let profile_key = StorageKey::UserProfile(user.clone());
+        let profile = Self::read_profile(&env, &profile_key).ok_or(Error::ProfileNotFound)?;
+
+        env.storage().persistent().remove(&profile_key);
+        env.storage().persistent().remove(&StorageKey::NicknameOwner(profile.nickname));
+
+        Self::emit_profile_deleted(&env, user);
+
+        Ok(())
+    }
+
+    // --- (View functions) ---
+
+    /// (View function) Retrieves a user's profile (if it exists).
+    pub fn get_profile(env: Env, user: Address) -> Option<UserProfile> {
+        let profile_key = StorageKey::UserProfile(user);
+
+        if let Some(profile) = Self::read_profile(&env, &profile_key) {
+            Self::bump_profile(&env, &profile_key);
+            Some(profile)
+        } else {
+            None
+        }
+    }
+
+    /// (View function) Same as `get_profile`, but never extends TTLs, so polling it does not
+    /// write to the ledger.
+    pub fn get_profile_readonly(env: Env, user: Address) -> Option<UserProfile> {
+        Self::read_profile(&env, &StorageKey::UserProfile(user))
+    }
+
+    /// (View function) Resolves a nickname to the address that owns it (if any).
+    pub fn resolve_nickname(env: Env, nickname: String) -> Option<Address> {
+        let owner_key = StorageKey::NicknameOwner(nickname);
+
+        if let Some(owner) = env.storage().persistent().get::<StorageKey, Address>(&owner_key) {
+            Self::bump_profile(&env, &owner_key);
+            Some(owner)
+        } else {
+            None
+        }
+    }
+
+    /// (View function) Returns whether `set_profile` would accept `nickname` from a new user:
+    /// it passes the same validation and nobody owns it yet. Never writes or extends TTLs.
+    pub fn nickname_available(env: Env, nickname: String) -> bool {
+        Self::validate_nickname(&env, &nickname).is_ok()
+            && !env.storage().persistent().has(&StorageKey::NicknameOwner(nickname))
+    }
+
+    /// (View function) Checks if a user is registered for the CURRENT active session, counting
+    /// check-ins made with any earlier code of the same event.
+    pub fn check_presence(env: Env, user: Address) -> Result<bool, Error> {
+        Self::require_initialized(&env)?;
+
+        let current_hash: BytesN<32> = match env.storage().persistent().get(&StorageKey::ActiveHash) {
+            Some(hash) => hash,
+            None => return Ok(false),
+        };
+
+        Self::bump_active_hash(&env);
+
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &current_hash), user);
+
+        let is_present = env.storage().persistent().has(&presence_key);
+
+        if is_present {
+            Self::bump_session(&env, &presence_key);
+        }
+
+        Ok(is_present)
+    }
+
+    /// (View function) Same as `check_presence`, but never extends TTLs.
+    pub fn check_presence_readonly(env: Env, user: Address) -> Result<bool, Error> {
+        Self::require_initialized(&env)?;
+
+        match env.storage().persistent().get::<StorageKey, BytesN<32>>(&StorageKey::ActiveHash) {
+            Some(current_hash) => {
+                let presence_key = StorageKey::Presence(Self::event_of(&env, &current_hash), user);
+                Ok(env.storage().persistent().has(&presence_key))
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// (View function) Checks presence in the CURRENT session by display name, for check-in
+    /// desks that know attendees by nickname. Unknown nicknames are reported as absent.
+    pub fn check_presence_by_nickname(env: Env, nickname: String) -> Result<bool, Error> {
+        match Self::resolve_nickname(env.clone(), nickname) {
+            Some(user) => Self::check_presence(env, user),
+            None => {
+                Self::require_initialized(&env)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// (View function) Returns how many users registered for a given session.
+    pub fn get_attendance_count(env: Env, session_hash: BytesN<32>) -> u32 {
+        let count_key = StorageKey::AttendanceCount(Self::event_of(&env, &session_hash));
+
+        if let Some(count) = env.storage().persistent().get::<StorageKey, u32>(&count_key) {
+            Self::bump_session(&env, &count_key);
+            count
+        } else {
+            0
+        }
+    }
+
+    /// (View function) Returns the receipt stored when the user checked in with `register` (or
+    /// another self check-in): `sha256(user.to_xdr() || session_hash || ledger_sequence)`, with
+    /// the sequence as 4 big-endian bytes. `None` for admin marks or withdrawn check-ins.
+    pub fn get_receipt(env: Env, user: Address, session_hash: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&StorageKeyExt::Receipt(Self::event_of(&env, &session_hash), user))
+    }
+
+    /// (View function) Checks if a user was registered for a specific session, active or not.
+    pub fn check_presence_for(env: Env, user: Address, session_hash: BytesN<32>) -> bool {
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &session_hash), user);
+
+        let is_present = env.storage().persistent().has(&presence_key);
+
+        if is_present {
+            Self::bump_session(&env, &presence_key);
+        }
+
+        is_present
+    }
+
+    /// (View function) Returns the ledger at which a user checked into the CURRENT active session.
+    pub fn get_checkin_time(env: Env, user: Address) -> Option<u32> {
+        let current_hash: BytesN<32> = env.storage().persistent().get(&StorageKey::ActiveHash)?;
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &current_hash), user);
+
+        let value: Val = env.storage().persistent().get(&presence_key)?;
+
+        Self::bump_session(&env, &presence_key);
+
+        Self::decode_presence(&env, &value).map(|record| record.at)
+    }
+
+    /// (View function) Returns when and by whom a user's presence in any session was recorded.
+    /// `marked_by` is the vouching admin for manual marks and `None` for self check-ins.
+    /// Entries written before check-in times were recorded report ledger 0.
+    pub fn get_presence_record(env: Env, user: Address, session_hash: BytesN<32>) -> Option<PresenceRecord> {
+        let presence_key = StorageKey::Presence(Self::event_of(&env, &session_hash), user);
+        let value: Val = env.storage().persistent().get(&presence_key)?;
+
+        Self::bump_session(&env, &presence_key);
+
+        Some(Self::decode_presence(&env, &value).unwrap_or(PresenceRecord { at: 0, marked_by: None }))
+    }
+
+    /// (View function) Returns the check-in ledger and the nickname the user had at check-in
+    /// time for any session (empty if they had no profile then). Entries written before
+    /// check-in times were recorded report ledger 0.
+    pub fn get_attendance_record(env: Env, user: Address, session_hash: BytesN<32>) -> Option<(u32, String)> {
+        let event_id = Self::event_of(&env, &session_hash);
+        let presence_key = StorageKey::Presence(event_id.clone(), user.clone());
+        let value: Val = env.storage().persistent().get(&presence_key)?;
+
+        Self::bump_session(&env, &presence_key);
+
+        let snapshot_key = StorageKey::PresenceNickname(event_id, user);
+        let nickname = if let Some(nickname) = env.storage().persistent().get::<StorageKey, String>(&snapshot_key) {
+            Self::bump_session(&env, &snapshot_key);
+            nickname
+        } else {
+            String::from_str(&env, "")
+        };
+
+        let at = Self::decode_presence(&env, &value).map(|record| record.at).unwrap_or(0);
+
+        Some((at, nickname))
+    }
+
+    /// (View function) Returns whether a badge was successfully minted for the user's check-in.
+    pub fn has_badge(env: Env, user: Address, session_hash: BytesN<32>) -> bool {
+        let badge_key = StorageKey::Badge(Self::event_of(&env, &session_hash), user);
+
+        let minted = env.storage().persistent().has(&badge_key);
+
+        if minted {
+            Self::bump_profile(&env, &badge_key);
+        }
+
+        minted
+    }
+
+    /// (View function) Returns the user's consecutive-session attendance streak.
+    pub fn get_streak(env: Env, user: Address) -> u32 {
+        let streak_key = StorageKey::Streak(user);
+
+        if let Some(streak) = env.storage().persistent().get::<StorageKey, u32>(&streak_key) {
+            Self::bump_profile(&env, &streak_key);
+            streak
+        } else {
+            0
+        }
+    }
+
+    /// (View function) Returns whether profile edits are locked by `set_profiles_locked`.
+    pub fn profiles_locked(env: Env) -> bool {
+        env.storage().instance().get(&StorageKeyExt::ProfilesLocked).unwrap_or(false)
+    }
+
+    /// (View function) Returns whether the contract is paused.
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&StorageKey::Paused).unwrap_or(false)
+    }
+
+    /// (View function) Returns everything a check-in screen needs in one call.
+    pub fn session_state(env: Env) -> SessionState {
+        let active_hash: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+
+        let (attendee_count, expires_at) = match &active_hash {
+            Some(hash) => (
+                env.storage().persistent().get(&StorageKey::AttendanceCount(Self::event_of(&env, hash))).unwrap_or(0),
+                env.storage().persistent().get(&StorageKey::SessionExpiry(hash.clone())),
+            ),
+            None => (0, None),
+        };
+
+        SessionState {
+            active_hash,
+            paused: Self::is_paused(env.clone()),
+            attendee_count,
+            expires_at,
+            open: Self::registration_open(env),
+        }
+    }
+
+    /// (View function) Returns how many check-ins the active session can still take, or `None`
+    /// when there is no active session or it has no capacity limit.
+    pub fn remaining_capacity(env: Env) -> Option<u32> {
+        let active_hash: BytesN<32> = env.storage().persistent().get(&StorageKey::ActiveHash)?;
+
+        let capacity = Self::session_capacity(&env, &active_hash);
+        if capacity == 0 {
+            return None;
+        }
+
+        let count: u32 = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::AttendanceCount(Self::event_of(&env, &active_hash)))
+        .unwrap_or(0);
+
+        Some(capacity.saturating_sub(count))
+    }
+
+    /// (View function) Returns the percentage (0 to 100) of a session's capacity already taken,
+    /// or `None` when the session does not exist or has no capacity limit.
+    pub fn fill_rate(env: Env, session_hash: BytesN<32>) -> Option<u32> {
+        if !env.storage().persistent().has(&StorageKey::SessionInfo(session_hash.clone())) {
+            return None;
+        }
+
+        let capacity = Self::session_capacity(&env, &session_hash);
+        if capacity == 0 {
+            return None;
+        }
+
+        let count: u32 = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::AttendanceCount(Self::event_of(&env, &session_hash)))
+        .unwrap_or(0);
+
+        // Admin marks can push the count past the limit
+        let rate = (count as u64 * 100 / capacity as u64).min(100);
+        Some(rate as u32)
+    }
+
+    /// (View function) Returns whether check-ins are accepted right now: a session is
+    /// active, the contract is not paused and the session has not expired.
+    pub fn registration_open(env: Env) -> bool {
+        if Self::require_not_paused(&env).is_err() {
+            return false;
+        }
+
+        match env.storage().persistent().get::<StorageKey, BytesN<32>>(&StorageKey::ActiveHash) {
+            Some(active_hash) => !Self::is_expired(&env, &active_hash),
+            None => false,
+        }
+    }
+
+    /// (View function) Returns the addresses that attended a session, in check-in order.
+    pub fn get_attendees(env: Env, session_hash: BytesN<32>) -> Vec<Address> {
+        let attendees_key = StorageKey::Attendees(Self::event_of(&env, &session_hash));
+
+        if let Some(attendees) = env.storage().persistent().get(&attendees_key) {
+            Self::bump_session(&env, &attendees_key);
+            attendees
+        } else {
+            Vec::new(&env)
+        }
+    }
+
+    /// (View function) Returns up to `limit` attendees of a session starting at `offset`, in
+    /// check-in order, so large events can be paged through (at most 100 per page).
+    pub fn get_attendees_page(env: Env, session_hash: BytesN<32>, offset: u32, limit: u32) -> Result<Vec<Address>, Error> {
+        if limit > Self::MAX_ATTENDEES_PAGE {
+            return Err(Error::LimitTooLarge);
+        }
+
+        let attendees_key = StorageKey::Attendees(Self::event_of(&env, &session_hash));
+        let attendees: Vec<Address> = match env.storage().persistent().get(&attendees_key) {
+            Some(attendees) => attendees,
+            None => return Ok(Vec::new(&env)),
+        };
+
+        Self::bump_session(&env, &attendees_key);
+
+        let start = offset.min(attendees.len());
+        let end = offset.saturating_add(limit).min(attendees.len());
+
+        Ok(attendees.slice(start..end))
+    }
+
+    /// (View function) Returns how many sessions the user has checked into over the contract's life.
+    pub fn get_total_checkins(env: Env, user: Address) -> u32 {
+        let total_key = StorageKey::TotalCheckins(user);
+
+        if let Some(total) = env.storage().persistent().get::<StorageKey, u32>(&total_key) {
+            Self::bump_profile(&env, &total_key);
+            total
+        } else {
+            0
+        }
+    }
+
+    /// (View function) Returns how many distinct addresses have ever checked in, counted once at
+    /// their first check-in (including admin marks) and not reduced by withdrawals.
+    pub fn total_unique_users(env: Env) -> u32 {
+        env.storage().instance().get(&StorageKey::UniqueUsers).unwrap_or(0)
+    }
+
+    /// (View function) Top-line numbers for dashboards in one call: sessions created, distinct
+    /// users, check-ins across all sessions (net of withdrawals) and whether a non-expired
+    /// session is active. Never extends TTLs, so it is cheap to poll.
+    pub fn stats(env: Env) -> Stats {
+        let active: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+
+        Stats {
+            session_count: Self::session_count(env.clone()),
+            total_unique_users: Self::total_unique_users(env.clone()),
+            total_checkins_all: env.storage().instance().get(&StorageKeyExt::TotalCheckinsAll).unwrap_or(0),
+            active: active.is_some_and(|hash| !Self::is_expired(&env, &hash)),
+        }
+    }
+
+    /// (View function) Returns how many check-ins were recorded on a UTC day, where `day_index`
+    /// is the ledger timestamp divided by 86400 (days since the Unix epoch). A user counts once
+    /// per event: withdrawing is not subtracted and checking in again is not added.
+    pub fn get_daily_count(env: Env, day_index: u32) -> u32 {
+        let daily_key = StorageKey::DailyCount(day_index);
+
+        if let Some(count) = env.storage().persistent().get::<StorageKey, u32>(&daily_key) {
+            Self::bump_profile(&env, &daily_key);
+            count
+        } else {
+            0
+        }
+    }
+
+    /// (View function) Returns the user's accumulated reward points.
+    pub fn get_points(env: Env, user: Address) -> u64 {
+        let points_key = StorageKey::Points(user);
+
+        if let Some(points) = env.storage().persistent().get::<StorageKey, u64>(&points_key) {
+            Self::bump_profile(&env, &points_key);
+            points
+        } else {
+            0
+        }
+    }
+
+    /// (View function) Returns a page of the leaderboard: participants ranked by points, descending,
+    /// ties keeping their first check-in order. All participants (at most 1000) are ranked before
+    /// paging; `limit` is capped at 50.
+    pub fn top_attendees(env: Env, offset: u32, limit: u32) -> Result<Vec<(Address, u64)>, Error> {
+        if limit > Self::MAX_LEADERBOARD_PAGE {
+            return Err(Error::LimitTooLarge);
+        }
+
+        let mut ranked: Vec<(Address, u64)> = Vec::new(&env);
+        let participants: Vec<Address> = match env.storage().persistent().get(&StorageKey::Participants) {
+            Some(participants) => participants,
+            None => return Ok(ranked),
+        };
+
+        Self::bump_profile(&env, &StorageKey::Participants);
+
+        if offset >= participants.len() || limit == 0 {
+            return Ok(ranked);
+        }
+
+        // Only the entries up to the end of the page are kept, so a page deep in the ranking costs more
+        let keep = offset.saturating_add(limit).min(participants.len());
+        for user in participants.iter() {
+            let points: u64 = env.storage().persistent().get(&StorageKey::Points(user.clone())).unwrap_or(0);
+
+            let mut index = ranked.len();
+            while index > 0 && ranked.get_unchecked(index - 1).1 < points {
+                index -= 1;
+            }
+            if index < keep {
+                ranked.insert(index, (user, points));
+                if ranked.len() > keep {
+                    ranked.pop_back();
+                }
+            }
+        }
+
+        Ok(ranked.slice(offset..keep))
+    }
+
+    /// (View function) Returns the current admin address.
+    /// With several admins this is the primary one (the first in the admin set).
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        Self::bump_instance(&env);
+
+        Self::require_initialized(&env)
+    }
+
+    /// (View function) Same as `get_admin`, but never extends TTLs.
+    pub fn get_admin_readonly(env: Env) -> Result<Address, Error> {
+        Self::require_initialized(&env)
+    }
+
+    /// (View function) Returns whether `who` holds the moderator role.
+    pub fn is_moderator(env: Env, who: Address) -> bool {
+        Self::load_moderators(&env).contains(&who)
+    }
+
+    /// (View function) Returns whether `who` may create sessions (an admin or a moderator), so
+    /// frontends need not hardcode the role rules. Works before any session exists.
+    pub fn can_create_session(env: Env, who: Address) -> bool {
+        Self::is_session_operator(&env, &who)
+    }
+
+    /// (View function) Returns whether `initialize` has run, without failing on fresh deployments.
+    pub fn is_initialized(env: Env) -> bool {
+        Self::load_admins(&env).is_ok()
+    }
+
+    /// (View function) Returns whether `who` is one of the admins.
+    pub fn is_admin(env: Env, who: Address) -> bool {
+        match Self::load_admins(&env) {
+            Ok(admins) => admins.contains(&who),
+            Err(_) => false,
+        }
+    }
+
+    /// (View function) Returns the contract interface version.
+    pub fn version(_env: Env) -> u32 {
+        Self::VERSION
+    }
+
+    /// (View function) Returns a human-readable message for an `Error` code, for thin clients
+    /// that do not bundle the contract spec. Unrecognized codes yield "Unknown error".
+    pub fn error_message(env: Env, code: u32) -> String {
+        let message = match Error::try_from(soroban_sdk::Error::from_contract_error(code)) {
+            Ok(error) => Self::describe_error(error),
+            Err(_) => "Unknown error",
+        };
+
+        String::from_str(&env, message)
+    }
+
+    /// (View function) Returns the contract version together with the primary admin.
+    pub fn contract_info(env: Env) -> Result<(u32, Address), Error> {
+        Ok((Self::VERSION, Self::get_admin(env)?))
+    }
+
+    /// (View function) Returns the current active session hash (if any).
+    /// Returns `None` once the session has expired.
+    pub fn get_session(env: Env) -> Option<BytesN<32>> {
+        if let Some(hash) = env.storage().persistent().get(&StorageKey::ActiveHash) {
+            if Self::is_expired(&env, &hash) {
+                return None;
+            }
+
+            Self::bump_active_hash(&env);
+            Some(hash)
+        } else {
+            None
+        }
+    }
+
+    /// (View function) Returns how many more ledgers the active session's storage is guaranteed
+    /// to live without further activity: a lower bound, as contracts cannot read actual TTLs.
+    /// Returns `None` when there is no active session, or none has been tracked yet.
+    pub fn session_ttl(env: Env) -> Option<u32> {
+        if !env.storage().persistent().has(&StorageKey::ActiveHash) {
+            return None;
+        }
+
+        let live_until: u32 = env.storage().instance().get(&StorageKey::ActiveHashLiveUntil)?;
+
+        Some(live_until.saturating_sub(env.ledger().sequence()))
+    }
+
+    /// (View function) Same as `get_session`, but never extends TTLs.
+    pub fn get_session_readonly(env: Env) -> Option<BytesN<32>> {
+        let hash: BytesN<32> = env.storage().persistent().get(&StorageKey::ActiveHash)?;
+
+        if Self::is_expired(&env, &hash) {
+            None
+        } else {
+            Some(hash)
+        }
+    }
+
+    /// (View function) Retrieves a session's metadata (if it exists).
+    pub fn get_session_info(env: Env, session_hash: BytesN<32>) -> Option<SessionInfo> {
+        let info_key = StorageKey::SessionInfo(session_hash);
+
+        if let Some(info) = Self::read_session_info(&env, &info_key) {
+            Self::bump_session(&env, &info_key);
+            Some(info)
+        } else {
+            None
+        }
+    }
+
+    /// (View function) Returns whether `session_hash` belongs to a session ever started here, even
+    /// after it was rotated out or closed, e.g. to validate QR codes or certificates. Sessions
+    /// whose metadata expired from storage are no longer recognized.
+    pub fn session_exists(env: Env, session_hash: BytesN<32>) -> bool {
+        env.storage().persistent().has(&StorageKey::SessionInfo(session_hash))
+    }
+
+    /// (View function) Returns whether `session_hash` was ended with `close_session`, as opposed
+    /// to rotated out or still active. Closed sessions refuse check-ins.
+    pub fn is_session_closed(env: Env, session_hash: BytesN<32>) -> bool {
+        env.storage().persistent().has(&StorageKey::ClosedSession(session_hash))
+    }
+
+    /// (View function) Returns how many sessions have been started over the contract's life.
+    pub fn session_count(env: Env) -> u32 {
+        env.storage().instance().get(&StorageKey::SessionIndex).unwrap_or(0)
+    }
+
+    /// (View function) Returns the retained session hashes in creation order, skipping
+    /// archived ones unless `include_archived` is set.
+    pub fn list_sessions(env: Env, include_archived: bool) -> Vec<BytesN<32>> {
+        let sessions: Vec<BytesN<32>> = match env.storage().persistent().get(&StorageKey::SessionList) {
+            Some(sessions) => sessions,
+            None => return Vec::new(&env),
+        };
+
+        Self::bump_session(&env, &StorageKey::SessionList);
+
+        if include_archived {
+            return sessions;
+        }
+
+        let mut visible = Vec::new(&env);
+        for hash in sessions.iter() {
+            if !env.storage().persistent().has(&StorageKey::Archived(hash.clone())) {
+                visible.push_back(hash);
+            }
+        }
+        visible
+    }
+
+    /// (View function) Check presence for multiple users at once.
+    pub fn check_batch(env: Env, users: Vec<Address>) -> Result<Vec<bool>, Error> {
+        Self::require_initialized(&env)?;
+
+        let current_hash: BytesN<32> = match env.storage().persistent().get(&StorageKey::ActiveHash) {
+            Some(hash) => hash,
+            None => return Ok(Vec::new(&env)),
+        };
+
+        Self::bump_active_hash(&env);
+
+        let event_id = Self::event_of(&env, &current_hash);
+
+        let mut results = Vec::new(&env);
+        for user in users.iter() {
+            let presence_key = StorageKey::Presence(event_id.clone(), user);
+
+            let is_present = env.storage().persistent().has(&presence_key);
+
+            if is_present {
+                Self::bump_session(&env, &presence_key);
+            }
+            results.push_back(is_present);
+        }
+        Ok(results)
+    }
+
+    /// (View function) The inverse of `check_batch`: reports whether one user attended each of
+    /// the given sessions (at most 50). Never extends TTLs, keeping the aggregate cheap.
+    pub fn my_attendance(env: Env, user: Address, session_hashes: Vec<BytesN<32>>) -> Result<Vec<bool>, Error> {
+        if session_hashes.len() > Self::MAX_ATTENDANCE_QUERY {
+            return Err(Error::LimitTooLarge);
+        }
+
+        let mut results = Vec::new(&env);
+        for hash in session_hashes.iter() {
+            let presence_key = StorageKey::Presence(Self::event_of(&env, &hash), user.clone());
+            results.push_back(env.storage().persistent().has(&presence_key));
+        }
+        Ok(results)
+    }
+
+    /// (View function) Reports whether the user attended any retained session created between
+    /// the two ledger sequences (inclusive). Only the `list_sessions` window is scanned, so older
+    /// sessions are not considered. Never extends TTLs.
+    pub fn attended_between(env: Env, user: Address, from_ledger: u32, to_ledger: u32) -> bool {
+        let sessions: Vec<BytesN<32>> = env.storage().persistent().get(&StorageKey::SessionList).unwrap_or(Vec::new(&env));
+
+        sessions.iter().any(|hash| {
+            let in_range = Self::read_session_info(&env, &StorageKey::SessionInfo(hash.clone()))
+            .is_some_and(|info| info.created_at >= from_ledger && info.created_at <= to_ledger);
+
+            in_range && env.storage().persistent().has(&StorageKey::Presence(Self::event_of(&env, &hash), user.clone()))
+        })
+    }
+
+    /// (View function) Counts the retained sessions (the `list_sessions` window, archived ones
+    /// included) the user attended, rotated codes of one event counting once. Unlike
+    /// `get_total_checkins`, evicted sessions no longer count. Never extends TTLs.
+    pub fn sessions_attended(env: Env, user: Address) -> u32 {
+        let sessions: Vec<BytesN<32>> = env.storage().persistent().get(&StorageKey::SessionList).unwrap_or(Vec::new(&env));
+
+        let mut attended: Vec<BytesN<32>> = Vec::new(&env);
+        for hash in sessions.iter() {
+            let event_id = Self::event_of(&env, &hash);
+            if attended.contains(&event_id) {
+                continue;
+            }
+
+            if env.storage().persistent().has(&StorageKey::Presence(event_id.clone(), user.clone())) {
+                attended.push_back(event_id);
+            }
+        }
+        attended.len()
+    }
+
+    /// (View function) Like `check_batch`, but pairs each presence flag with the user's
+    /// nickname (empty if the user has no profile). Every user is reported as absent
+    /// when no session is active.
+    pub fn check_batch_detailed(env: Env, users: Vec<Address>) -> Result<Vec<(bool, String)>, Error> {
+        Self::require_initialized(&env)?;
+
+        let current_hash: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+
+        if current_hash.is_some() {
+            Self::bump_active_hash(&env);
+        }
+
+        let event_id = current_hash.map(|hash| Self::event_of(&env, &hash));
+
+        let mut results = Vec::new(&env);
+        for user in users.iter() {
+            let is_present = match &event_id {
+                Some(event_id) => {
+                    let presence_key = StorageKey::Presence(event_id.clone(), user.clone());
+                    let is_present = env.storage().persistent().has(&presence_key);
+
+                    if is_present {
+                        Self::bump_session(&env, &presence_key);
+                    }
+                    is_present
+                }
+                None => false,
+            };
+
+            results.push_back((is_present, Self::nickname_of(&env, &user)));
+        }
+        Ok(results)
+    }
+}
+
+// --- Internal Helpers ---
+impl AttendanceContract {
+    fn default_ttl_config() -> TtlConfig {
+        TtlConfig {
+            threshold: Self::TTL_THRESHOLD,
+            session_bump: Self::TTL_BUMP_30D,
+            profile_bump: Self::TTL_BUMP_90D,
+        }
+    }
+
+    fn ttl_config(env: &Env) -> TtlConfig {
+        env.storage()
+        .instance()
+        .get(&StorageKey::TtlConfig)
+        .unwrap_or(Self::default_ttl_config())
+    }
+
+    /// Extends the instance TTL (admin data and contract-wide settings).
+    fn bump_instance(env: &Env) {
+        let config = Self::ttl_config(env);
+        env.storage().instance().extend_ttl(config.threshold, config.session_bump);
+    }
+
+    /// Extends the TTL of session-scoped persistent data.
+    fn bump_session<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+        let config = Self::ttl_config(env);
+        env.storage().persistent().extend_ttl(key, config.threshold, config.session_bump);
+    }
+
+    /// Extends the TTL of the active hash and tracks a lower bound of its live-until ledger,
+    /// since contracts cannot read TTLs. While the bound is still above `threshold` the TTL is
+    /// too, so nothing is extended or written; views only write once the bound runs low.
+    fn bump_active_hash(env: &Env) {
+        let config = Self::ttl_config(env);
+        let now = env.ledger().sequence();
+        let tracked: u32 = env.storage().instance().get(&StorageKey::ActiveHashLiveUntil).unwrap_or(0);
+        if tracked >= now.saturating_add(config.threshold) {
+            return;
+        }
+
+        // Using the bump as threshold guarantees the entry lives `session_bump` more ledgers afterwards
+        env.storage().persistent().extend_ttl(&StorageKey::ActiveHash, config.session_bump, config.session_bump);
+
+        let floor = now.saturating_add(config.session_bump).min(env.ledger().max_live_until_ledger());
+        env.storage().instance().set(&StorageKey::ActiveHashLiveUntil, &floor);
+    }
+
+    /// Extends the TTL of long-lived user data (profiles, points, streaks).
+    fn bump_profile<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+        let config = Self::ttl_config(env);
+        env.storage().persistent().extend_ttl(key, config.threshold, config.profile_bump);
+    }
+
+    /// Loads the admin set. Deployments from before multi-admin support only hold `Admin`.
+    fn load_admins(env: &Env) -> Result<Vec<Address>, Error> {
+        if let Some(admins) = env.storage().instance().get(&StorageKey::Admins) {
+            return Ok(admins);
+        }
+
+        let admin: Address = env.storage().instance()
+        .get(&StorageKey::Admin)
+        .ok_or(Error::NotInitialized)?;
+
+        Ok(Vec::from_array(env, [admin]))
+    }
+
+    /// Returns the primary admin, or `NotInitialized` before `initialize` has run.
+    fn require_initialized(env: &Env) -> Result<Address, Error> {
+        Ok(Self::load_admins(env)?.get_unchecked(0))
+    }
+
+    /// Makes `new_admin` the primary admin (index 0) and drops any pending proposal.
+    /// Naming the current primary leaves the admin list and history untouched.
+    fn replace_primary_admin(env: &Env, new_admin: &Address) -> Result<(), Error> {
+        let mut admins = Self::load_admins(env)?;
+
+        if admins.get_unchecked(0) == *new_admin {
+            env.storage().instance().remove(&StorageKey::PendingAdmin);
+            return Ok(());
+        }
+
+        // Contracts initialized before the history existed start it with their current primary
+        if !env.storage().instance().has(&StorageKeyExt::AdminHistory) {
+            Self::push_admin_history(env, &admins.get_unchecked(0));
+        }
+        Self::push_admin_history(env, new_admin);
+
+        // A secondary admin being promoted is moved rather than duplicated
+        if let Some(index) = admins.first_index_of(new_admin) {
+            admins.remove(index);
+        }
+        admins.set(0, new_admin.clone());
+        Self::save_admins(env, &admins);
+
+        env.storage().instance().remove(&StorageKey::PendingAdmin);
+
+        Ok(())
+    }
+
+    /// Appends a primary admin to the history, dropping the oldest entries beyond the cap.
+    fn push_admin_history(env: &Env, admin: &Address) {
+        let mut history: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&StorageKeyExt::AdminHistory)
+        .unwrap_or(Vec::new(env));
+
+        history.push_back(admin.clone());
+        while history.len() > Self::MAX_ADMIN_HISTORY {
+            history.pop_front();
+        }
+
+        env.storage().instance().set(&StorageKeyExt::AdminHistory, &history);
+    }
+
+    fn save_admins(env: &Env, admins: &Vec<Address>) {
+        env.storage().instance().set(&StorageKey::Admins, admins);
+        env.storage().instance().remove(&StorageKey::Admin);
+
+        Self::bump_instance(env);
+    }
+
+    /// Checks that `admin` belongs to the admin set and requires its authorization.
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+        if !Self::load_admins(env)?.contains(admin) {
+            return Err(Error::NotAdmin);
+        }
+
+        admin.require_auth();
+
+        Ok(())
+    }
+
+    /// Like `require_admin`, but also accepts moderators.
+    fn require_moderator_or_admin(env: &Env, caller: &Address) -> Result<(), Error> {
+        Self::require_initialized(env)?;
+
+        if !Self::is_session_operator(env, caller) {
+            return Err(Error::NotAdmin);
+        }
+
+        caller.require_auth();
+
+        Ok(())
+    }
+
+    /// Whether `who` may run session operations: any admin or moderator.
+    fn is_session_operator(env: &Env, who: &Address) -> bool {
+        Self::is_admin(env.clone(), who.clone()) || Self::load_moderators(env).contains(who)
+    }
+
+    fn load_moderators(env: &Env) -> Vec<Address> {
+        env.storage().instance().get(&StorageKey::Moderators).unwrap_or(Vec::new(env))
+    }
+
+    /// Validates `submitted_hash` against the active session and records `user` as present.
+    fn record_presence(env: &Env, user: Address, submitted_hash: BytesN<32>) -> Result<(), Error> {
+        let stored_hash = Self::validate_checkin(env, &user, &submitted_hash)?;
+
+        // Withdrawals keep this marker, so re-registering for the same event earns nothing
+        let rewarded_key = StorageKeyExt::Rewarded(Self::event_of(env, &stored_hash), user.clone());
+        let rewarded = env.storage().persistent().has(&rewarded_key);
+
+        // Every event of an opted-out check-in, failures included, carries the placeholder address
+        let opted_out = env.storage().persistent().has(&StorageKey::OptedOut(user.clone()));
+        let event_user = Self::event_identity(env, &user);
+
+        // The payout runs before any write, so a reverted transfer leaves no partial check-in behind
+        if !rewarded {
+            Self::pay_token_reward(env, &user, &event_user)?;
+        }
+
+        Self::bump_active_hash(env);
+
+        let now = env.ledger().sequence();
+        let last_key = StorageKey::LastCheckin(user.clone());
+
+        let (count, first_checkin) = Self::write_presence(env, &stored_hash, &user, None)?;
+
+        env.storage().persistent().set(&last_key, &now);
+
+        Self::bump_session(env, &last_key);
+
+        let receipt = Self::compute_receipt(env, &user, &stored_hash, now);
+        let receipt_key = StorageKeyExt::Receipt(Self::event_of(env, &stored_hash), user.clone());
+        env.storage().persistent().set(&receipt_key, &receipt);
+
+        Self::bump_session(env, &receipt_key);
+
+        let streak = Self::update_streak(env, &user);
+
+        let points_earned = if rewarded { 0 } else { Self::award_points(env, &user, &stored_hash) };
+        env.storage().persistent().set(&rewarded_key, &true);
+
+        Self::bump_session(env, &rewarded_key);
+
+        // Like points and payouts, the badge belongs to the first check-in of the event
+        if !rewarded {
+            Self::mint_badge(env, &user, &stored_hash, &event_user);
+        }
+
+        // The receipt could be matched against candidate addresses, so it is withheld too
+        let (nickname, event_receipt) = if opted_out {
+            (String::from_str(env, ""), BytesN::from_array(env, &[0u8; 32]))
+        } else {
+            (Self::nickname_of(env, &user), receipt)
+        };
+
+        Self::emit_present(
+            env,
+            event_user.clone(),
+            stored_hash.clone(),
+            nickname,
+            (count, streak, points_earned),
+            event_receipt,
+        );
+
+        if first_checkin {
+            Self::emit_first_checkin(env, event_user, stored_hash);
+        }
+
+        Ok(())
+    }
+
+    /// Runs every check a self check-in must pass without writing anything, and returns the
+    /// active hash.
+    fn validate_checkin(env: &Env, user: &Address, submitted_hash: &BytesN<32>) -> Result<BytesN<32>, Error> {
+        Self::require_initialized(env)?;
+
+        Self::require_not_paused(env)?;
+
+        let stored_hash: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::ActiveHash)
+        .ok_or(Error::NoActiveSession)?;
+
+        if Self::is_expired(env, &stored_hash) {
+            return Err(Error::SessionExpired);
+        }
+
+        if env.storage().persistent().has(&StorageKey::ClosedSession(stored_hash.clone())) {
+            return Err(Error::NoActiveSession);
+        }
+
+        if *submitted_hash != stored_hash {
+            return Err(Error::IncorrectHash);
+        }
+
+        let admin_can_attend: bool = env.storage().instance().get(&StorageKey::AdminCanAttend).unwrap_or(true);
+        if !admin_can_attend && Self::load_admins(env)?.contains(user) {
+            return Err(Error::AdminCannotAttend);
+        }
+
+        let require_profile: bool = env.storage().instance().get(&StorageKey::RequireProfile).unwrap_or(false);
+        if require_profile && !env.storage().persistent().has(&StorageKey::UserProfile(user.clone())) {
+            return Err(Error::ProfileRequired);
+        }
+
+        // Rotated codes of the same event share one presence entry
+        let presence_key = StorageKey::Presence(Self::event_of(env, &stored_hash), user.clone());
+
+        if env.storage().persistent().has(&presence_key) {
+            return Err(Error::AlreadyRegistered);
+        }
+
+        let cooldown: u32 = env.storage().instance().get(&StorageKey::CheckinCooldown).unwrap_or(0);
+        if cooldown > 0 {
+            if let Some(last) = env.storage().persistent().get::<StorageKey, u32>(&StorageKey::LastCheckin(user.clone())) {
+                if env.ledger().sequence() < last.saturating_add(cooldown) {
+                    return Err(Error::CheckinTooSoon);
+                }
+            }
+        }
+
+        Self::require_capacity(env, &stored_hash)?;
+
+        Ok(stored_hash)
+    }
+
+    /// Credits the configured points-per-check-in to `user` and returns the amount awarded.
+    fn award_points(env: &Env, user: &Address, session_hash: &BytesN<32>) -> u64 {
+        let per_checkin: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::PointsPerCheckin)
+        .unwrap_or(Self::DEFAULT_POINTS_PER_CHECKIN);
+
+        let weight = Self::read_session_info(env, &StorageKey::SessionInfo(session_hash.clone()))
+        .map(|info| info.weight)
+        .unwrap_or(Self::DEFAULT_SESSION_WEIGHT);
+        let amount = per_checkin.saturating_mul(weight as u64);
+
+        Self::add_points(env, user, amount);
+
+        amount
+    }
+
+    /// Transfers the configured token reward to `user`, if any. A failed transfer is reported
+    /// with `reward_fail` instead of reverting the check-in unless the config says otherwise.
+    fn pay_token_reward(env: &Env, user: &Address, event_user: &Address) -> Result<(), Error> {
+        let config: RewardConfig = match env.storage().instance().get(&StorageKey::RewardToken) {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        if config.amount == 0 {
+            return Ok(());
+        }
+
+        let client = token::TokenClient::new(env, &config.token);
+        let paid = matches!(
+            client.try_transfer(&env.current_contract_address(), user, &config.amount),
+            Ok(Ok(()))
+        );
+
+        if !paid {
+            if config.revert_on_failure {
+                return Err(Error::RewardTransferFailed);
+            }
+
+            Self::emit_reward_failed(env, event_user.clone(), config.token, config.amount);
+        }
+
+        Ok(())
+    }
+
+    /// Mints the session badge through the configured NFT contract, if any. Presence is kept
+    /// when minting fails; `badge_fail` is emitted instead, naming `event_user`.
+    fn mint_badge(env: &Env, user: &Address, session_hash: &BytesN<32>, event_user: &Address) {
+        let nft: Address = match env.storage().instance().get(&StorageKey::BadgeContract) {
+            Some(nft) => nft,
+            None => return,
+        };
+
+        let args: Vec<Val> = Vec::from_array(env, [user.into_val(env), session_hash.into_val(env)]);
+        let minted = matches!(
+            env.try_invoke_contract::<(), soroban_sdk::Error>(&nft, &symbol_short!("mint"), args),
+            Ok(Ok(()))
+        );
+
+        if minted {
+            let badge_key = StorageKey::Badge(Self::event_of(env, session_hash), user.clone());
+            env.storage().persistent().set(&badge_key, &true);
+
+            Self::bump_profile(env, &badge_key);
+        } else {
+            Self::emit_badge_failed(env, event_user.clone(), session_hash.clone());
+        }
+    }
+
+    /// Credits `amount` points to `user` and returns their new total.
+    fn add_points(env: &Env, user: &Address, amount: u64) -> u64 {
+        let points_key = StorageKey::Points(user.clone());
+        let points: Option<u64> = env.storage().persistent().get(&points_key);
+        if points.is_none() {
+            Self::add_participant(env, user);
+        }
+
+        let total = points.unwrap_or(0).saturating_add(amount);
+        env.storage().persistent().set(&points_key, &total);
+
+        Self::bump_profile(env, &points_key);
+
+        total
+    }
+
+    /// Adds `user` to the leaderboard participants. Once the list is full, newcomers
+    /// still earn points but are not listed.
+    fn add_participant(env: &Env, user: &Address) {
+        let mut participants: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::Participants)
+        .unwrap_or(Vec::new(env));
+
+        if participants.len() >= Self::MAX_PARTICIPANTS {
+            return;
+        }
+
+        participants.push_back(user.clone());
+        env.storage().persistent().set(&StorageKey::Participants, &participants);
+
+        Self::bump_profile(env, &StorageKey::Participants);
+    }
+
+    /// Decodes a stored presence value. Records used to be stored as the bare check-in ledger,
+    /// and before that as `true`; the latter carries no ledger and decodes to `None`.
+    fn decode_presence(env: &Env, value: &Val) -> Option<PresenceRecord> {
+        if let Ok(record) = PresenceRecord::try_from_val(env, value) {
+            return Some(record);
+        }
+
+        u32::try_from_val(env, value).ok().map(|at| PresenceRecord { at, marked_by: None })
+    }
+
+    /// `sha256(user.to_xdr() || session_hash || ledger_sequence as 4 big-endian bytes)`.
+    fn compute_receipt(env: &Env, user: &Address, session_hash: &BytesN<32>, ledger_sequence: u32) -> BytesN<32> {
+        let mut preimage = user.clone().to_xdr(env);
+        preimage.append(&Bytes::from(session_hash.clone()));
+        preimage.append(&Bytes::from_array(env, &ledger_sequence.to_be_bytes()));
+
+        BytesN::from(env.crypto().sha256(&preimage))
+    }
+
+    /// Adds an event to the purge queue unless it is already there, returning false when the
+    /// queue is full.
+    fn queue_purge(queue: &mut Vec<BytesN<32>>, event_id: BytesN<32>) -> bool {
+        if queue.contains(&event_id) {
+            return true;
+        }
+        if queue.len() >= Self::MAX_PURGE_QUEUE {
+            return false;
+        }
+
+        queue.push_back(event_id);
+
+        true
+    }
+
+    /// Deletes one attendee's per-event entries, returning whether a presence was removed.
+    fn purge_attendee(env: &Env, event_id: &BytesN<32>, user: Address) -> bool {
+        env.storage().persistent().remove(&StorageKey::PresenceNickname(event_id.clone(), user.clone()));
+        env.storage().persistent().remove(&StorageKey::Claimed(event_id.clone(), user.clone()));
+        env.storage().persistent().remove(&StorageKeyExt::Receipt(event_id.clone(), user.clone()));
+
+        let presence_key = StorageKey::Presence(event_id.clone(), user);
+        if env.storage().persistent().has(&presence_key) {
+            env.storage().persistent().remove(&presence_key);
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the session's attendee limit, 0 meaning unlimited.
+    fn session_capacity(env: &Env, session_hash: &BytesN<32>) -> u32 {
+        env.storage().persistent().get(&StorageKey::SessionCapacity(session_hash.clone())).unwrap_or(0)
+    }
+
+    /// Fails with `SessionFull` once the event's attendee list or the session's capacity is exhausted.
+    fn require_capacity(env: &Env, session_hash: &BytesN<32>) -> Result<(), Error> {
+        let event_id = Self::event_of(env, session_hash);
+
+        let attendees: u32 = env
+        .storage()
+        .persistent()
+        .get::<StorageKey, Vec<Address>>(&StorageKey::Attendees(event_id.clone()))
+        .map(|attendees| attendees.len())
+        .unwrap_or(0);
+        if attendees >= Self::MAX_ATTENDEES {
+            return Err(Error::SessionFull);
+        }
+
+        let count: u32 = env.storage().persistent().get(&StorageKey::AttendanceCount(event_id)).unwrap_or(0);
+        let capacity = Self::session_capacity(env, session_hash);
+        if capacity > 0 && count >= capacity {
+            return Err(Error::SessionFull);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the presence entry for `user`, appends it to the attendee list and bumps the
+    /// session's attendance count, the user's lifetime total and today's check-in total.
+    /// Returns the updated count and whether this is the user's first presence anywhere, so
+    /// every caller can follow its own event with `first_in`.
+    /// `marked_by` is the admin vouching for the user, or `None` for self check-ins.
+    fn write_presence(
+        env: &Env,
+        session_hash: &BytesN<32>,
+        user: &Address,
+        marked_by: Option<Address>,
+    ) -> Result<(u32, bool), Error> {
+        Self::require_capacity(env, session_hash)?;
+
+        let event_id = Self::event_of(env, session_hash);
+
+        let attendees_key = StorageKey::Attendees(event_id.clone());
+        let mut attendees: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&attendees_key)
+        .unwrap_or(Vec::new(env));
+
+        let count_key = StorageKey::AttendanceCount(event_id.clone());
+        let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+
+        let presence_key = StorageKey::Presence(event_id.clone(), user.clone());
+
+        let record = PresenceRecord {
+            at: env.ledger().sequence(),
+            marked_by,
+        };
+        env.storage().persistent().set(&presence_key, &record);
+
+        Self::bump_session(env, &presence_key);
+
+        // Certificates need the name used at check-in time, not whatever the user renames to later
+        let nickname = Self::nickname_of(env, user);
+        if !nickname.is_empty() {
+            let snapshot_key = StorageKey::PresenceNickname(event_id, user.clone());
+            env.storage().persistent().set(&snapshot_key, &nickname);
+
+            Self::bump_session(env, &snapshot_key);
+        }
+
+        attendees.push_back(user.clone());
+        env.storage().persistent().set(&attendees_key, &attendees);
+
+        Self::bump_session(env, &attendees_key);
+
+        let count = count + 1;
+        env.storage().persistent().set(&count_key, &count);
+
+        Self::bump_session(env, &count_key);
+
+        let total_key = StorageKey::TotalCheckins(user.clone());
+        let previous: Option<u32> = env.storage().persistent().get(&total_key);
+        env.storage().persistent().set(&total_key, &(previous.unwrap_or(0) + 1));
+
+        Self::bump_profile(env, &total_key);
+
+        // Withdrawn check-ins keep the entry at zero, so only a missing one marks a new user
+        if previous.is_none() {
+            let unique_users: u32 = env.storage().instance().get(&StorageKey::UniqueUsers).unwrap_or(0);
+            env.storage().instance().set(&StorageKey::UniqueUsers, &unique_users.saturating_add(1));
+        }
+
+        let checkins_all: u32 = env.storage().instance().get(&StorageKeyExt::TotalCheckinsAll).unwrap_or(0);
+        env.storage().instance().set(&StorageKeyExt::TotalCheckinsAll, &checkins_all.saturating_add(1));
+
+        // Re-registering after a withdrawal is churn, not attendance, so it is not counted again
+        if !env.storage().persistent().has(&StorageKeyExt::Rewarded(Self::event_of(env, session_hash), user.clone())) {
+            let daily_key = StorageKey::DailyCount(Self::day_index(env));
+            let daily: u32 = env.storage().persistent().get(&daily_key).unwrap_or(0);
+            env.storage().persistent().set(&daily_key, &(daily + 1));
+
+            // Reporting reads these long after the sessions are gone
+            Self::bump_profile(env, &daily_key);
+        }
+
+        Ok((count, previous.is_none()))
+    }
+
+    /// Removes the presence entry for `user`, drops it from the attendee list and
+    /// decrements the session's attendance count and the user's lifetime total.
+    fn clear_presence(env: &Env, session_hash: &BytesN<32>, user: &Address) {
+        let event_id = Self::event_of(env, session_hash);
+
+        env.storage().persistent().remove(&StorageKey::Presence(event_id.clone(), user.clone()));
+        env.storage().persistent().remove(&StorageKey::PresenceNickname(event_id.clone(), user.clone()));
+        env.storage().persistent().remove(&StorageKeyExt::Receipt(event_id.clone(), user.clone()));
+
+        let attendees_key = StorageKey::Attendees(event_id.clone());
+        if let Some(mut attendees) = env.storage().persistent().get::<StorageKey, Vec<Address>>(&attendees_key) {
+            if let Some(index) = attendees.first_index_of(user) {
+                attendees.remove(index);
+                env.storage().persistent().set(&attendees_key, &attendees);
+            }
+        }
+
+        let count_key = StorageKey::AttendanceCount(event_id);
+        let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        env.storage().persistent().set(&count_key, &count.saturating_sub(1));
+
+        Self::bump_session(env, &count_key);
+
+        // The entry is kept even at zero so it still records that the user once checked in
+        let total_key = StorageKey::TotalCheckins(user.clone());
+        if let Some(total) = env.storage().persistent().get::<StorageKey, u32>(&total_key) {
+            env.storage().persistent().set(&total_key, &total.saturating_sub(1));
+        }
+
+        let checkins_all: u32 = env.storage().instance().get(&StorageKeyExt::TotalCheckinsAll).unwrap_or(0);
+        env.storage().instance().set(&StorageKeyExt::TotalCheckinsAll, &checkins_all.saturating_sub(1));
+    }
+
+    /// Advances the user's streak for the current session index and returns it.
+    /// The streak grows when the user attended the previous session and resets to 1 otherwise.
+    fn update_streak(env: &Env, user: &Address) -> u32 {
+        let session_index: u32 = env.storage().instance().get(&StorageKey::SessionIndex).unwrap_or(0);
+
+        let last_key = StorageKey::LastSessionIndex(user.clone());
+        let streak_key = StorageKey::Streak(user.clone());
+
+        let last_index: Option<u32> = env.storage().persistent().get(&last_key);
+        let streak: u32 = env.storage().persistent().get(&streak_key).unwrap_or(0);
+
+        let new_streak = match last_index {
+            // Re-registering after a deregistration in the same session keeps the streak
+            Some(last) if last == session_index => streak,
+            Some(last) if last + 1 == session_index => streak + 1,
+            _ => 1,
+        };
+
+        env.storage().persistent().set(&last_key, &session_index);
+        env.storage().persistent().set(&streak_key, &new_streak);
+
+        Self::bump_profile(env, &last_key);
+        Self::bump_profile(env, &streak_key);
+
+        new_streak
+    }
+
+    /// Returns the UTC day of the current ledger, counted in days since the Unix epoch.
+    fn day_index(env: &Env) -> u32 {
+        (env.ledger().timestamp() / Self::SECONDS_PER_DAY) as u32
+    }
+
+    /// Fails with `ContractPaused` while the admin has paused the contract.
+    fn require_not_paused(env: &Env) -> Result<(), Error> {
+        if env.storage().instance().get(&StorageKey::Paused).unwrap_or(false) {
+            return Err(Error::ContractPaused);
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the session has an expiry ledger that is already behind us.
+    fn is_expired(env: &Env, session_hash: &BytesN<32>) -> bool {
+        let expiry_key = StorageKey::SessionExpiry(session_hash.clone());
+
+        match env.storage().persistent().get::<StorageKey, u32>(&expiry_key) {
+            Some(expires_at) => env.ledger().sequence() > expires_at,
+            None => false,
+        }
+    }
+
+    /// Checks the nickname length against the configured bounds.
+    fn validate_nickname(env: &Env, nickname: &String) -> Result<(), Error> {
+        let (min, max): (u32, u32) = env
+        .storage()
+        .instance()
+        .get(&StorageKey::NicknameBounds)
+        .unwrap_or((Self::DEFAULT_NICKNAME_MIN, Self::DEFAULT_NICKNAME_MAX));
+
+        if nickname.len() < min || nickname.len() > max {
+            return Err(Error::InvalidNickname);
+        }
+
+        // Control characters break UI rendering and event parsing; padding spaces enable look-alikes
+        let bytes = nickname.to_bytes();
+        if bytes.iter().any(|b| b < 0x20 || b == 0x7f) {
+            return Err(Error::InvalidNickname);
+        }
+        if bytes.first() == Some(b' ') || bytes.last() == Some(b' ') {
+            return Err(Error::InvalidNickname);
+        }
+
+        Ok(())
+    }
+
+    /// Reads stored session metadata; sessions created before weights existed count as weight 1
+    /// and sessions created before events or memos existed are their own event with no memo.
+    /// Creators were not tracked at first; such sessions have no `created_by`.
+    fn read_session_info(env: &Env, info_key: &StorageKey) -> Option<SessionInfo> {
+        let raw: Map<Symbol, Val> = env.storage().persistent().get(info_key)?;
+
+        let name = raw
+            .get(Symbol::new(env, "name"))
+            .and_then(|v| String::try_from_val(env, &v).ok())?;
+        let created_at = raw
+            .get(Symbol::new(env, "created_at"))
+            .and_then(|v| u32::try_from_val(env, &v).ok())
+            .unwrap_or(0);
+        let hash = raw
+            .get(Symbol::new(env, "hash"))
+            .and_then(|v| BytesN::<32>::try_from_val(env, &v).ok())?;
+        let weight = raw
+            .get(Symbol::new(env, "weight"))
+            .and_then(|v| u32::try_from_val(env, &v).ok())
+            .unwrap_or(Self::DEFAULT_SESSION_WEIGHT);
+        let event_id = raw
+            .get(Symbol::new(env, "event_id"))
+            .and_then(|v| BytesN::<32>::try_from_val(env, &v).ok())
+            .unwrap_or(hash.clone());
+        let memo = raw
+            .get(Symbol::new(env, "memo"))
+            .and_then(|v| String::try_from_val(env, &v).ok())
+            .unwrap_or(String::from_str(env, ""));
+        let created_by = raw
+            .get(Symbol::new(env, "created_by"))
+            .and_then(|v| Address::try_from_val(env, &v).ok());
+
+        Some(SessionInfo { name, created_at, hash, weight, event_id, memo, created_by })
+    }
+
+    /// Returns the logical event a session hash belongs to. Presence, attendee lists, counts,
+    /// claims and badges are keyed by it, so rotated hashes of one event share them.
+    /// Sessions not started through `set_hash_for_event` are their own event.
+    fn event_of(env: &Env, session_hash: &BytesN<32>) -> BytesN<32> {
+        Self::read_session_info(env, &StorageKey::SessionInfo(session_hash.clone()))
+        .map(|info| info.event_id)
+        .unwrap_or(session_hash.clone())
+    }
+
+    /// Returns the user's nickname, or an empty string when they have no profile.
+    fn nickname_of(env: &Env, user: &Address) -> String {
+        let profile_key = StorageKey::UserProfile(user.clone());
+
+        if let Some(profile) = Self::read_profile(env, &profile_key) {
+            Self::bump_profile(env, &profile_key);
+            profile.nickname
+        } else {
+            String::from_str(env, "")
+        }
+    }
+
+    /// Reads a stored profile, defaulting fields added after it was written
+    /// (profiles saved before `avatar_uri` existed decode with an empty URI, before
+    /// `last_updated` existed with `registered_at`, and before `registered_timestamp` with 0).
+    fn read_profile(env: &Env, profile_key: &StorageKey) -> Option<UserProfile> {
+        let raw: Map<Symbol, Val> = env.storage().persistent().get(profile_key)?;
+
+        let nickname = raw
+            .get(Symbol::new(env, "nickname"))
+            .and_then(|v| String::try_from_val(env, &v).ok())?;
+        let registered_at = raw
+            .get(Symbol::new(env, "registered_at"))
+            .and_then(|v| u32::try_from_val(env, &v).ok())
+            .unwrap_or(0);
+        let avatar_uri = raw
+            .get(Symbol::new(env, "avatar_uri"))
+            .and_then(|v| String::try_from_val(env, &v).ok())
+            .unwrap_or_else(|| String::from_str(env, ""));
+
+        let last_updated = raw
+            .get(Symbol::new(env, "last_updated"))
+            .and_then(|v| u32::try_from_val(env, &v).ok())
+            .unwrap_or(registered_at);
+
+        let registered_timestamp = raw
+            .get(Symbol::new(env, "registered_timestamp"))
+            .and_then(|v| u64::try_from_val(env, &v).ok())
+            .unwrap_or(0);
+
+        let bio = raw
+            .get(Symbol::new(env, "bio"))
+            .and_then(|v| String::try_from_val(env, &v).ok())
+            .unwrap_or_else(|| String::from_str(env, ""));
+
+        Some(UserProfile { nickname, registered_at, avatar_uri, last_updated, registered_timestamp, bio })
+    }
+
+    /// Fails with `ProfilesLocked` while the admin has frozen profile edits.
+    fn require_profiles_unlocked(env: &Env) -> Result<(), Error> {
+        if Self::profiles_locked(env.clone()) {
+            return Err(Error::ProfilesLocked);
+        }
+
+        Ok(())
+    }
+
+    /// Fails with `ProfileUpdateTooSoon` while the profile is within its update cooldown.
+    fn require_profile_cooldown(env: &Env, profile: &UserProfile) -> Result<(), Error> {
+        let cooldown: u32 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::ProfileCooldown)
+        .unwrap_or(Self::DEFAULT_PROFILE_COOLDOWN);
+
+        if env.ledger().sequence() < profile.last_updated.saturating_add(cooldown) {
+            return Err(Error::ProfileUpdateTooSoon);
+        }
+
+        Ok(())
+    }
+
+    /// Reserves `nickname` for `user`, releasing the user's previous nickname.
+    /// Fails with `NicknameTaken` if another address already owns it.
+    fn claim_nickname(env: &Env, user: &Address, nickname: &String) -> Result<(), Error> {
+        let owner_key = StorageKey::NicknameOwner(nickname.clone());
+
+        if let Some(owner) = env.storage().persistent().get::<StorageKey, Address>(&owner_key) {
+            if owner != *user {
+                return Err(Error::NicknameTaken);
+            }
+        }
+
+        let profile_key = StorageKey::UserProfile(user.clone());
+        if let Some(old) = Self::read_profile(env, &profile_key) {
+            if old.nickname != *nickname {
+                env.storage().persistent().remove(&StorageKey::NicknameOwner(old.nickname));
+            }
+        }
+
+        env.storage().persistent().set(&owner_key, user);
+
+        Self::bump_profile(env, &owner_key);
+
+        Ok(())
+    }
+
+    /// Sets `user`'s nickname on behalf of `admin`, creating the profile if needed and
+    /// restarting the user's update cooldown.
+    fn override_nickname(env: &Env, admin: &Address, user: &Address, nickname: &String) -> Result<(), Error> {
+        Self::validate_nickname(env, nickname)?;
+
+        Self::claim_nickname(env, user, nickname)?;
+
+        let now = env.ledger().sequence();
+        let profile_key = StorageKey::UserProfile(user.clone());
+        let profile = match Self::read_profile(env, &profile_key) {
+            Some(previous) => UserProfile {
+                nickname: nickname.clone(),
+                last_updated: now,
+                ..previous
+            },
+            None => UserProfile {
+                nickname: nickname.clone(),
+                registered_at: now,
+                avatar_uri: String::from_str(env, ""),
+                last_updated: now,
+                registered_timestamp: env.ledger().timestamp(),
+                bio: String::from_str(env, ""),
+            },
+        };
+
+        env.storage().persistent().set(&profile_key, &profile);
+
+        Self::bump_profile(env, &profile_key);
+
+        Self::emit_admin_rename(env, admin.clone(), user.clone(), nickname.clone());
+
+        Ok(())
+    }
+
+    /// The match is exhaustive so a new `Error` variant cannot ship without a message.
+    fn describe_error(error: Error) -> &'static str {
+        match error {
+            Error::AlreadyInitialized => "Contract already initialized",
+            Error::NotInitialized => "Contract not initialized",
+            Error::NoActiveSession => "No active session",
+            Error::IncorrectHash => "Incorrect hash",
+            Error::AlreadyRegistered => "Already registered for this session",
+            Error::InvalidNickname => "Invalid nickname",
+            Error::NotRegistered => "Not registered for this session",
+            Error::InvalidName => "Invalid session name",
+            Error::NicknameTaken => "Nickname already taken",
+            Error::ProfileNotFound => "Profile not found",
+            Error::ContractPaused => "Contract is paused",
+            Error::SessionExpired => "Session has expired",
+            Error::SessionFull => "Session is full",
+            Error::InvalidConfig => "Invalid configuration",
+            Error::CannotRemoveLastAdmin => "Cannot remove the last admin",
+            Error::NotAdmin => "Caller is not an admin",
+            Error::LimitTooLarge => "Limit too large",
+            Error::NonceReused => "Nonce already used",
+            Error::NoCommitment => "No commitment submitted",
+            Error::AdminCannotAttend => "Admins cannot check in",
+            Error::ProfileRequired => "Profile required before checking in",
+            Error::InvalidAvatarUri => "Invalid avatar URI",
+            Error::CannotPurgeActive => "Cannot purge the active session",
+            Error::NoPendingAdmin => "No pending admin transfer",
+            Error::ProfileUpdateTooSoon => "Profile updated too recently",
+            Error::InvalidAdmin => "Invalid admin address",
+            Error::InvalidWeight => "Invalid session weight",
+            Error::SessionStillActive => "A session is still active",
+            Error::AlreadyClaimed => "Reward already claimed",
+            Error::RewardTransferFailed => "Reward transfer failed",
+            Error::NotModerator => "Address is not a moderator",
+            Error::InvalidHash => "Invalid hash",
+            Error::MustAttendFirst => "Must check in before creating a profile",
+            Error::InvalidMemo => "Invalid session memo",
+            Error::HashAlreadyUsed => "Session hash already used",
+            Error::CheckinTooSoon => "Checked in too recently",
+            Error::ProfileAlreadyExists => "Destination already has a profile",
+            Error::NoRewardToken => "No reward token configured",
+            Error::InvalidBio => "Invalid bio",
+            Error::ProfilesLocked => "Profile edits are locked",
+            Error::CheckedInActiveEvent => "Checked in to the active event",
+        }
+    }
+
+    fn is_zero_hash(hash: &BytesN<32>) -> bool {
+        hash.to_array() == [0u8; 32]
+    }
+
+    /// Activates `new_hash` as part of `event_id`, records its metadata (including the admin or
+    /// moderator who created it) and appends it to the session list. In strict mode it refuses
+    /// to replace a session that is still active.
+    /// Rotating the hash of the active event keeps the session index, so streaks are unaffected.
+    fn start_session(
+        env: &Env,
+        creator: &Address,
+        new_hash: BytesN<32>,
+        name: String,
+        weight: u32,
+        event_id: BytesN<32>,
+        memo: String,
+    ) -> Result<(), Error> {
+        // An all-zero hash is indistinguishable from an uninitialized value
+        if Self::is_zero_hash(&new_hash) || Self::is_zero_hash(&event_id) {
+            return Err(Error::InvalidHash);
+        }
+
+        // A reused hash would share presence entries with the earlier session
+        if env.storage().persistent().has(&StorageKey::SessionInfo(new_hash.clone())) {
+            return Err(Error::HashAlreadyUsed);
+        }
+
+        let active_hash: Option<BytesN<32>> = env.storage().persistent().get(&StorageKey::ActiveHash);
+        let active_event = active_hash.as_ref().map(|hash| Self::event_of(env, hash));
+        let rotation = active_event.as_ref() == Some(&event_id);
+
+        let strict: bool = env.storage().instance().get(&StorageKey::StrictSessions).unwrap_or(false);
+        let fresh_entry = active_hash.is_none();
+        if strict && !fresh_entry && !rotation {
+            return Err(Error::SessionStillActive);
+        }
+
+        let session_index = if rotation {
+            Self::session_count(env.clone())
+        } else {
+            Self::session_count(env.clone()) + 1
+        };
+        env.storage().instance().set(&StorageKey::SessionIndex, &session_index);
+
+        Self::bump_instance(env);
+
+        // Let indexers finalize a session that is being replaced while still active
+        if let (Some(old_hash), Some(old_event)) = (active_hash, active_event) {
+            let attendee_count: u32 = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::AttendanceCount(old_event))
+            .unwrap_or(0);
+
+            Self::emit_rotate(env, old_hash, new_hash.clone(), attendee_count);
+        }
+
+        if fresh_entry {
+            // A fresh entry must not inherit the bound tracked for an earlier one
+            env.storage().instance().remove(&StorageKey::ActiveHashLiveUntil);
+        }
+        env.storage().persistent().set(&StorageKey::ActiveHash, &new_hash);
+
+        Self::bump_active_hash(env);
+
+        let info_key = StorageKey::SessionInfo(new_hash.clone());
+        let info = SessionInfo {
+            name,
+            created_at: env.ledger().sequence(),
+            hash: new_hash.clone(),
+            weight,
+            event_id,
+            memo: memo.clone(),
+            created_by: Some(creator.clone()),
+        };
+        env.storage().persistent().set(&info_key, &info);
+        // A hash reused after its metadata expired must not inherit the expiry, capacity or closing
+        // of the earlier session
+        env.storage().persistent().remove(&StorageKey::SessionExpiry(new_hash.clone()));
+        env.storage().persistent().remove(&StorageKey::SessionCapacity(new_hash.clone()));
+        env.storage().persistent().remove(&StorageKey::ClosedSession(new_hash.clone()));
+
+        let duration: u32 = env.storage().instance().get(&StorageKey::DefaultSessionDuration).unwrap_or(0);
+        if duration > 0 {
+            let expiry_key = StorageKey::SessionExpiry(new_hash.clone());
+            env.storage().persistent().set(&expiry_key, &env.ledger().sequence().saturating_add(duration));
+
+            Self::bump_session(env, &expiry_key);
+        }
+
+        Self::bump_session(env, &info_key);
+
+        let mut sessions: Vec<BytesN<32>> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::SessionList)
+        .unwrap_or(Vec::new(env));
+
+        sessions.push_back(new_hash.clone());
+
+        let retained: u32 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::MaxSessionsRetained)
+        .unwrap_or(Self::MAX_SESSIONS);
+        // Purging happens later through `purge_evicted`; a lowered limit drains over several calls
+        let mut evicted_count: u32 = 0;
+        let mut queue: Vec<BytesN<32>> = Vec::new(env);
+        let mut queue_loaded = false;
+        while sessions.len() > retained && evicted_count < Self::MAX_EVICTIONS_PER_CALL {
+            if !queue_loaded {
+                queue = env.storage().persistent().get(&StorageKeyExt::PurgeQueue).unwrap_or(Vec::new(env));
+                queue_loaded = true;
+            }
+
+            let evicted = sessions.pop_front_unchecked();
+            env.storage().persistent().remove(&StorageKey::Archived(evicted.clone()));
+            let evicted_event = Self::event_of(env, &evicted);
+
+            // Rotated codes of an event that is still retained keep its attendance alive, and a
+            // full queue leaves the data to expire with its TTL instead
+            let shared = sessions.iter().any(|hash| Self::event_of(env, &hash) == evicted_event);
+            let queued = !shared && Self::queue_purge(&mut queue, evicted_event);
+
+            Self::emit_evict(env, evicted, queued);
+            evicted_count += 1;
+        }
+
+        if queue_loaded {
+            env.storage().persistent().set(&StorageKeyExt::PurgeQueue, &queue);
+
+            Self::bump_session(env, &StorageKeyExt::PurgeQueue);
+        }
+
+        env.storage().persistent().set(&StorageKey::SessionList, &sessions);
+
+        Self::bump_session(env, &StorageKey::SessionList);
+
+        Self::emit_new_session(env, new_hash, session_index, memo);
+
+        Ok(())
+    }
+}
+
+// --- Event Helpers ---
+// Every event topic is `(name, version)`, or `(prefix, name, version)` when `initialize` was given
+// an event prefix. Bump an event's version whenever its data shape changes.
+impl AttendanceContract {
+    const EVENT_V1: Symbol = symbol_short!("v1");
+    const EVENT_V2: Symbol = symbol_short!("v2");
+    const EVENT_V3: Symbol = symbol_short!("v3");
+    const EVENT_V4: Symbol = symbol_short!("v4");
+
+    // `Events::publish` is deprecated in SDK 23 in favour of `#[contractevent]` types.
+    #[allow(deprecated)]
+    fn publish_event<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, version: Symbol, data: D) {
+        match env.storage().instance().get::<StorageKeyExt, Symbol>(&StorageKeyExt::EventPrefix) {
+            Some(prefix) => env.events().publish((prefix, name, version), data),
+            None => env.events().publish((name, version), data),
+        }
+    }
+
+    fn emit_init(env: &Env, admin: Address) {
+        Self::publish_event(env, symbol_short!("init"), Self::EVENT_V1, (admin,));
+    }
+
+    /// v2 lacked `memo`; v1 carried only the hash.
+    fn emit_new_session(env: &Env, session_hash: BytesN<32>, session_index: u32, memo: String) {
+        Self::publish_event(env, symbol_short!("new_sess"), Self::EVENT_V3, (session_hash, session_index, memo));
+    }
+
+    fn emit_rotate(env: &Env, old_hash: BytesN<32>, new_hash: BytesN<32>, attendee_count: u32) {
+        Self::publish_event(env, symbol_short!("rotate"), Self::EVENT_V1, (old_hash, new_hash, attendee_count));
+    }
+
+    fn emit_close(env: &Env, session_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("close"), Self::EVENT_V1, (session_hash,));
+    }
+
+    fn emit_summary(env: &Env, session_hash: BytesN<32>, total_attendees: u32, created_at: u32, closed_at: u32) {
+        Self::publish_event(
+            env,
+            symbol_short!("summary"),
+            Self::EVENT_V1,
+            (session_hash, total_attendees, created_at, closed_at),
+        );
+    }
+
+    /// v4: `(user, session_hash, nickname, count, streak, points_earned, receipt)`, with
+    /// `(count, streak, points_earned)` passed as `tallies`. v3 lacked `receipt`, v2 also
+    /// `points_earned`; v1 carried only the first three fields.
+    fn emit_present(
+        env: &Env,
+        user: Address,
+        session_hash: BytesN<32>,
+        nickname: String,
+        tallies: (u32, u32, u64),
+        receipt: BytesN<32>,
+    ) {
+        let (count, streak, points_earned) = tallies;
+        Self::publish_event(
+            env,
+            symbol_short!("present"),
+            Self::EVENT_V4,
+            (user, session_hash, nickname, count, streak, points_earned, receipt),
+        );
+    }
+
+    /// The address events should carry for `user`: the placeholder account once they opted out.
+    fn event_identity(env: &Env, user: &Address) -> Address {
+        if env.storage().persistent().has(&StorageKey::OptedOut(user.clone())) {
+            Address::from_str(env, Self::ANONYMOUS_ACCOUNT)
+        } else {
+            user.clone()
+        }
+    }
+
+    fn emit_first_checkin(env: &Env, user: Address, session_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("first_in"), Self::EVENT_V1, (user, session_hash));
+    }
+
+    fn emit_decay(env: &Env, user: Address, removed: u64, total: u64) {
+        Self::publish_event(env, symbol_short!("decay"), Self::EVENT_V1, (user, removed, total));
+    }
+
+    fn emit_reward_claimed(env: &Env, user: Address, session_hash: BytesN<32>, amount: u64, total: u64) {
+        Self::publish_event(env, symbol_short!("reward"), Self::EVENT_V1, (user, session_hash, amount, total));
+    }
+
+    fn emit_withdraw(env: &Env, admin: Address, to: Address, token: Address, amount: i128) {
+        Self::publish_event(env, symbol_short!("withdraw"), Self::EVENT_V1, (admin, to, token, amount));
+    }
+
+    fn emit_reward_failed(env: &Env, user: Address, token: Address, amount: i128) {
+        Self::publish_event(env, Symbol::new(env, "reward_fail"), Self::EVENT_V1, (user, token, amount));
+    }
+
+    fn emit_badge_failed(env: &Env, user: Address, session_hash: BytesN<32>) {
+        Self::publish_event(env, Symbol::new(env, "badge_fail"), Self::EVENT_V1, (user, session_hash));
+    }
+
+    fn emit_dereg(env: &Env, user: Address, session_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("dereg"), Self::EVENT_V1, (user, session_hash));
+    }
+
+    /// v2: `(admin, user, session_hash, nickname)`; v1 lacked `nickname`.
+    fn emit_admin_mark(env: &Env, admin: Address, user: Address, session_hash: BytesN<32>, nickname: String) {
+        Self::publish_event(env, symbol_short!("adm_mark"), Self::EVENT_V2, (admin, user, session_hash, nickname));
+    }
+
+    fn emit_admin_rename(env: &Env, admin: Address, user: Address, nickname: String) {
+        Self::publish_event(env, Symbol::new(env, "adm_rename"), Self::EVENT_V1, (admin, user, nickname));
+    }
+
+    /// v2: `(admin, user, session_hash, nickname)`; v1 lacked `nickname`.
+    fn emit_admin_clear(env: &Env, admin: Address, user: Address, session_hash: BytesN<32>, nickname: String) {
+        Self::publish_event(env, symbol_short!("adm_clr"), Self::EVENT_V2, (admin, user, session_hash, nickname));
+    }
+
+    fn emit_admin_clear_session(env: &Env, admin: Address, session_hash: BytesN<32>, cleared: u32, remaining: u32) {
+        Self::publish_event(env, symbol_short!("adm_reset"), Self::EVENT_V2, (admin, session_hash, cleared, remaining));
+    }
+
+    fn emit_extend(env: &Env, admin: Address, session_hash: BytesN<32>, expires_at: u32) {
+        Self::publish_event(env, symbol_short!("extend"), Self::EVENT_V1, (admin, session_hash, expires_at));
+    }
+
+    fn emit_archive(env: &Env, admin: Address, session_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("archive"), Self::EVENT_V1, (admin, session_hash));
+    }
+
+    fn emit_admin_purge(env: &Env, admin: Address, session_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("adm_purge"), Self::EVENT_V2, (admin, session_hash));
+    }
+
+    fn emit_evict(env: &Env, session_hash: BytesN<32>, queued: bool) {
+        Self::publish_event(env, symbol_short!("evict"), Self::EVENT_V2, (session_hash, queued));
+    }
+
+    fn emit_purged(env: &Env, event_id: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("purged"), Self::EVENT_V1, (event_id,));
+    }
+
+    fn emit_upgrade(env: &Env, admin: Address, new_wasm_hash: BytesN<32>) {
+        Self::publish_event(env, symbol_short!("upgrade"), Self::EVENT_V1, (admin, new_wasm_hash));
+    }
+
+    fn emit_admin_transfer(env: &Env, old_admin: Address, new_admin: Address) {
+        Self::publish_event(env, symbol_short!("adm_xfer"), Self::EVENT_V1, (old_admin, new_admin));
+    }
+
+    fn emit_admin_proposed(env: &Env, admin: Address, new_admin: Address) {
+        Self::publish_event(env, symbol_short!("adm_prop"), Self::EVENT_V1, (admin, new_admin));
+    }
+
+    fn emit_admin_accepted(env: &Env, old_admin: Address, new_admin: Address) {
+        Self::publish_event(env, symbol_short!("adm_acc"), Self::EVENT_V1, (old_admin, new_admin));
+    }
+
+    fn emit_admin_added(env: &Env, admin: Address, new_admin: Address) {
+        Self::publish_event(env, symbol_short!("adm_add"), Self::EVENT_V1, (admin, new_admin));
+    }
+
+    fn emit_admin_removed(env: &Env, admin: Address, removed: Address) {
+        Self::publish_event(env, symbol_short!("adm_rm"), Self::EVENT_V1, (admin, removed));
+    }
+
+    fn emit_moderator_added(env: &Env, admin: Address, moderator: Address) {
+        Self::publish_event(env, symbol_short!("mod_add"), Self::EVENT_V1, (admin, moderator));
+    }
+
+    fn emit_moderator_removed(env: &Env, admin: Address, moderator: Address) {
+        Self::publish_event(env, symbol_short!("mod_rm"), Self::EVENT_V1, (admin, moderator));
+    }
+
+    fn emit_paused(env: &Env, admin: Address, paused: bool) {
+        let name = if paused { symbol_short!("paused") } else { symbol_short!("unpaused") };
+        Self::publish_event(env, name, Self::EVENT_V1, (admin,));
+    }
+
+    /// Unified audit trail for admin setters: `value` is the new setting as stored.
+    fn emit_config_changed(env: &Env, admin: Address, key: Symbol, value: Val) {
+        Self::publish_event(env, symbol_short!("cfg_chg"), Self::EVENT_V1, (admin, key, value));
+    }
+
+    /// v4: `(user, nickname, avatar_uri, registered_timestamp, bio)`, used only when the bio is
+    /// set; profiles without one keep the v3 shape `(user, nickname, avatar_uri, registered_timestamp)`.
+    /// v2 lacked `registered_timestamp`; v1 carried only `(user, nickname)`.
+    fn emit_profile(env: &Env, user: Address, profile: UserProfile) {
+        if profile.bio.is_empty() {
+            Self::publish_event(
+                env,
+                symbol_short!("profile"),
+                Self::EVENT_V3,
+                (user, profile.nickname, profile.avatar_uri, profile.registered_timestamp),
+            );
+        } else {
+            Self::publish_event(
+                env,
+                symbol_short!("profile"),
+                Self::EVENT_V4,
+                (user, profile.nickname, profile.avatar_uri, profile.registered_timestamp, profile.bio),
+            );
+        }
+    }
+
+    fn emit_profile_migrated(env: &Env, old: Address, new: Address) {
+        Self::publish_event(env, symbol_short!("prof_mig"), Self::EVENT_V1, (old, new));
+    }
+
+    fn emit_profile_deleted(env: &Env, user: Address) {
+        Self::publish_event(env, symbol_short!("prof_del"), Self::EVENT_V1, (user,));
+    }
+}
+
+mod test;
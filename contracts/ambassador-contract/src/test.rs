@@ -1,21 +1,2886 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{vec, Env, String};
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+    vec, Env, String,
+};
+
+fn setup(env: &Env) -> (AttendanceContractClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin, &None);
+
+    (client, admin)
+}
+
+fn session_hash(env: &Env, seed: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[seed; 32])
+}
+
+// Per-transaction write-entry limit of the network, which batch caps have to fit in
+const MAX_WRITE_ENTRIES: u32 = 50;
+
+fn numbered_nickname(env: &Env, prefix: u8, index: u32) -> String {
+    String::from_bytes(env, &[prefix, b'u', b's', b'e', b'r', b'a' + (index / 26) as u8, b'a' + (index % 26) as u8])
+}
+
+// Stand-in for an external badge NFT contract; minting fails once `set_failing(true)` is called
+#[contract]
+pub struct MockBadge;
+
+#[contractimpl]
+impl MockBadge {
+    pub fn mint(env: Env, _to: Address, _session_hash: BytesN<32>) {
+        if env.storage().instance().get(&symbol_short!("failing")).unwrap_or(false) {
+            panic!("minting disabled");
+        }
+        let minted: u32 = env.storage().instance().get(&symbol_short!("minted")).unwrap_or(0);
+        env.storage().instance().set(&symbol_short!("minted"), &(minted + 1));
+    }
+
+    pub fn minted(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("minted")).unwrap_or(0)
+    }
+
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage().instance().set(&symbol_short!("failing"), &failing);
+    }
+}
+
+#[test]
+fn test_attendance_count() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    assert_eq!(client.get_attendance_count(&hash), 0);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &hash);
+    client.register(&bob, &hash);
+    assert_eq!(client.get_attendance_count(&hash), 2);
+
+    assert_eq!(client.try_register(&alice, &hash), Err(Ok(Error::AlreadyRegistered)));
+    assert_eq!(client.get_attendance_count(&hash), 2);
+}
+
+#[test]
+fn test_deregister() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_deregister(&alice), Err(Ok(Error::NotRegistered)));
+
+    client.register(&alice, &hash);
+    assert!(client.check_presence(&alice));
+
+    client.deregister(&alice);
+    assert!(!client.check_presence(&alice));
+    assert_eq!(client.get_attendance_count(&hash), 0);
+
+    client.register(&alice, &hash);
+    assert_eq!(client.get_attendance_count(&hash), 1);
+}
+
+#[test]
+fn test_list_sessions() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.list_sessions(&false).len(), 0);
+
+    for seed in 1..=AttendanceContract::MAX_SESSIONS + 1 {
+        client.set_hash(&admin, &session_hash(&env, seed as u8));
+    }
+
+    let sessions = client.list_sessions(&false);
+    assert_eq!(sessions.len(), AttendanceContract::MAX_SESSIONS);
+    assert_eq!(sessions.first(), Some(session_hash(&env, 2)));
+    assert_eq!(
+        sessions.last(),
+        Some(session_hash(&env, (AttendanceContract::MAX_SESSIONS + 1) as u8))
+    );
+}
+
+#[test]
+fn test_session_info() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    let name = String::from_str(&env, "Workshop");
+    client.set_hash_named(&admin, &hash, &name);
+
+    let info = client.get_session_info(&hash).unwrap();
+    assert_eq!(info.name, name);
+    assert_eq!(info.hash, hash);
+    assert_eq!(info.created_at, env.ledger().sequence());
+    assert_eq!(client.get_session(), Some(hash));
+
+    let short = String::from_str(&env, "ab");
+    assert_eq!(
+        client.try_set_hash_named(&admin, &session_hash(&env, 2), &short),
+        Err(Ok(Error::InvalidName))
+    );
+    assert_eq!(client.get_session_info(&session_hash(&env, 2)), None);
+}
+
+#[test]
+fn test_close_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.try_close_session(&admin), Err(Ok(Error::NoActiveSession)));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.close_session(&admin);
+
+    assert_eq!(client.get_session(), None);
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_register(&alice, &hash), Err(Ok(Error::NoActiveSession)));
+    assert!(client.is_session_closed(&hash));
+    assert!(!client.is_session_closed(&session_hash(&env, 2)));
+
+    // Rotated-out sessions are not closed
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.set_hash(&admin, &session_hash(&env, 3));
+    assert!(!client.is_session_closed(&session_hash(&env, 2)));
+}
+
+#[test]
+fn test_checkin_time() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.get_checkin_time(&alice), None);
+
+    env.ledger().set_sequence_number(42);
+    client.register(&alice, &hash);
+    assert_eq!(client.get_checkin_time(&alice), Some(42));
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_register_batch() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    client.register(&carol, &hash);
+
+    let entries = vec![
+        &env,
+        (alice.clone(), hash.clone()),
+        (bob.clone(), session_hash(&env, 9)),
+        (carol.clone(), hash.clone()),
+    ];
+    let results = client.register_batch(&entries);
+
+    assert_eq!(
+        results,
+        vec![&env, Ok(()), Err(Error::IncorrectHash), Err(Error::AlreadyRegistered)]
+    );
+    assert!(client.check_presence(&alice));
+    assert!(!client.check_presence(&bob));
+    assert_eq!(client.get_attendance_count(&hash), 2);
+}
+
+#[test]
+fn test_register_batch_at_cap() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    // Token rewards, badges and profiles make every entry as costly as it gets
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    soroban_sdk::token::StellarAssetClient::new(&env, &sac.address()).mint(&client.address, &1_000);
+    client.set_reward_token(&admin, &sac.address(), &100, &false);
+    client.set_badge_contract(&admin, &env.register(MockBadge, ()));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let mut entries = Vec::new(&env);
+    let empty = String::from_str(&env, "");
+    for index in 0..AttendanceContract::MAX_REGISTER_BATCH {
+        let user = Address::generate(&env);
+        client.set_profile(&user, &numbered_nickname(&env, b'b', index), &empty, &empty);
+        entries.push_back((user, hash.clone()));
+    }
+
+    let results = client.register_batch(&entries);
+    assert!(env.cost_estimate().resources().write_entries <= MAX_WRITE_ENTRIES);
+    assert!(results.iter().all(|result| result.is_ok()));
+
+    entries.push_back((Address::generate(&env), hash));
+    assert_eq!(client.try_register_batch(&entries), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_admin_mark_present() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_admin_mark_present(&admin, &alice), Err(Ok(Error::NoActiveSession)));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.admin_mark_present(&admin, &alice);
+
+    assert!(client.check_presence(&alice));
+    assert_eq!(client.get_attendance_count(&hash), 1);
+    assert_eq!(client.try_admin_mark_present(&admin, &alice), Err(Ok(Error::AlreadyRegistered)));
+    assert_eq!(client.try_register(&alice, &hash), Err(Ok(Error::AlreadyRegistered)));
+}
+
+#[test]
+fn test_streak() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.get_streak(&alice), 0);
+
+    for seed in 1..=3 {
+        let hash = session_hash(&env, seed);
+        client.set_hash(&admin, &hash);
+        client.register(&alice, &hash);
+    }
+    assert_eq!(client.get_streak(&alice), 3);
+
+    // Skipping a session resets the streak
+    client.set_hash(&admin, &session_hash(&env, 4));
+    let hash = session_hash(&env, 5);
+    client.set_hash(&admin, &hash);
+    client.register(&alice, &hash);
+    assert_eq!(client.get_streak(&alice), 1);
+}
+
+#[test]
+fn test_nickname_uniqueness() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_profile_cooldown(&admin, &0);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let first = String::from_str(&env, "Satoshi");
+    let second = String::from_str(&env, "Hal");
+
+    client.set_profile(&alice, &first, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert_eq!(client.resolve_nickname(&first), Some(alice.clone()));
+    assert_eq!(client.try_set_profile(&bob, &first, &String::from_str(&env, ""), &String::from_str(&env, "")), Err(Ok(Error::NicknameTaken)));
+
+    // Renaming releases the old nickname
+    client.set_profile(&alice, &second, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert_eq!(client.resolve_nickname(&first), None);
+    assert_eq!(client.resolve_nickname(&second), Some(alice.clone()));
+
+    client.set_profile(&bob, &first, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert_eq!(client.resolve_nickname(&first), Some(bob));
+}
+
+#[test]
+fn test_delete_profile() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_delete_profile(&alice), Err(Ok(Error::ProfileNotFound)));
+
+    let nickname = String::from_str(&env, "Satoshi");
+    client.set_profile(&alice, &nickname, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.delete_profile(&alice);
+
+    assert_eq!(client.get_profile(&alice), None);
+    assert_eq!(client.resolve_nickname(&nickname), None);
+}
+
+#[test]
+fn test_paused() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    client.set_paused(&admin, &true);
+    assert!(client.is_paused());
+
+    assert_eq!(client.try_register(&alice, &hash), Err(Ok(Error::ContractPaused)));
+    assert_eq!(
+        client.try_set_profile(&alice, &String::from_str(&env, "Satoshi"), &String::from_str(&env, ""), &String::from_str(&env, "")),
+        Err(Ok(Error::ContractPaused))
+    );
+    assert_eq!(
+        client.register_batch(&vec![&env, (alice.clone(), hash.clone())]),
+        vec![&env, Err(Error::ContractPaused)]
+    );
+    assert!(!client.check_presence(&alice));
+
+    client.set_paused(&admin, &false);
+    client.register(&alice, &hash);
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_session_expiry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    env.ledger().set_sequence_number(100);
+    client.set_hash_with_expiry(&admin, &hash, &110);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    env.ledger().set_sequence_number(110);
+    client.register(&alice, &hash);
+    assert_eq!(client.get_session(), Some(hash.clone()));
+
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.try_register(&bob, &hash), Err(Ok(Error::SessionExpired)));
+    assert_eq!(client.get_session(), None);
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_check_presence_for() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    let second = session_hash(&env, 2);
+    let alice = Address::generate(&env);
+
+    client.set_hash(&admin, &first);
+    client.register(&alice, &first);
+    client.set_hash(&admin, &second);
+
+    assert!(!client.check_presence(&alice));
+    assert!(client.check_presence_for(&alice, &first));
+    assert!(!client.check_presence_for(&alice, &second));
+}
+
+#[test]
+fn test_event_versions() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("new_sess"), symbol_short!("v3")).into_val(&env));
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &hash);
+    // A debut check-in is followed by `first_in`
+    let events = env.events().all();
+    let (_, topics, _) = events.get(events.len() - 2).unwrap();
+    assert_eq!(topics, (symbol_short!("present"), symbol_short!("v4")).into_val(&env));
+}
+
+#[test]
+fn test_attendees() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    client.register(&alice, &hash);
+    client.admin_mark_present(&admin, &bob);
+    client.register(&carol, &hash);
+    assert_eq!(
+        client.get_attendees(&hash),
+        vec![&env, alice.clone(), bob.clone(), carol.clone()]
+    );
+
+    client.deregister(&bob);
+    assert_eq!(client.get_attendees(&hash), vec![&env, alice, carol]);
+    assert_eq!(client.get_attendees(&session_hash(&env, 2)).len(), 0);
+}
+
+#[test]
+fn test_nickname_bounds() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let long = String::from_str(&env, "A Very Long Ambassador Display Name");
+    assert_eq!(client.try_set_profile(&alice, &long, &String::from_str(&env, ""), &String::from_str(&env, "")), Err(Ok(Error::InvalidNickname)));
+
+    client.set_nickname_bounds(&admin, &1, &64);
+    client.set_profile_cooldown(&admin, &0);
+    client.set_profile(&alice, &long, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.set_profile(&alice, &String::from_str(&env, "A"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+
+    assert_eq!(client.try_set_nickname_bounds(&admin, &0, &10), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.try_set_nickname_bounds(&admin, &5, &65), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.try_set_nickname_bounds(&admin, &10, &5), Err(Ok(Error::InvalidConfig)));
+}
+
+#[test]
+fn test_multi_admin() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let staff = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_set_hash(&staff, &session_hash(&env, 1)),
+        Err(Ok(Error::NotAdmin))
+    );
+
+    client.add_admin(&admin, &staff);
+    assert!(client.is_admin(&staff));
+    assert!(!client.is_admin(&outsider));
+    client.set_hash(&staff, &session_hash(&env, 1));
+
+    // Any admin can remove another, but never the last one
+    client.remove_admin(&staff, &admin);
+    assert_eq!(client.get_admin(), staff);
+    assert_eq!(client.try_remove_admin(&staff, &staff), Err(Ok(Error::CannotRemoveLastAdmin)));
+    assert_eq!(client.try_remove_admin(&staff, &outsider), Err(Ok(Error::NotAdmin)));
+}
+
+#[test]
+fn test_legacy_admin_key() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(&env, &contract_id);
+
+    // Deployments from before multi-admin support stored a single `Admin`
+    let admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().instance().set(&StorageKey::Admin, &admin);
+    });
+
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.try_initialize(&admin, &None), Err(Ok(Error::AlreadyInitialized)));
+
+    let staff = Address::generate(&env);
+    client.add_admin(&admin, &staff);
+    assert!(client.is_admin(&admin));
+    assert!(client.is_admin(&staff));
+}
+
+#[test]
+fn test_admin_clear_presence() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.set_hash(&admin, &first);
+    client.register(&alice, &first);
+    client.register(&bob, &first);
+    client.set_hash(&admin, &session_hash(&env, 2));
+
+    client.admin_clear_presence(&admin, &alice, &first);
+    assert!(!client.check_presence_for(&alice, &first));
+    assert!(client.check_presence_for(&bob, &first));
+    assert_eq!(client.get_attendance_count(&first), 1);
+    assert_eq!(client.get_attendees(&first), vec![&env, bob]);
+
+    assert_eq!(
+        client.try_admin_clear_presence(&admin, &alice, &first),
+        Err(Ok(Error::NotRegistered))
+    );
+}
+
+#[test]
+fn test_contract_info() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.version(), AttendanceContract::VERSION);
+    assert_eq!(client.contract_info(), (AttendanceContract::VERSION, admin));
+}
+
+#[test]
+fn test_points() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+    client.register(&alice, &first);
+    assert_eq!(client.get_points(&alice), 10);
+
+    // No points for a rejected duplicate check-in
+    let _ = client.try_register(&alice, &first);
+    assert_eq!(client.get_points(&alice), 10);
+
+    client.set_points_per_checkin(&admin, &25);
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &second);
+    client.register(&alice, &second);
+    assert_eq!(client.get_points(&alice), 35);
+}
+
+#[test]
+fn test_top_attendees() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+
+    for seed in 1..=3 {
+        let hash = session_hash(&env, seed);
+        client.set_hash(&admin, &hash);
+        client.register(&bob, &hash);
+        if seed > 1 {
+            client.register(&carol, &hash);
+        }
+        if seed == 3 {
+            client.register(&alice, &hash);
+        }
+    }
+
+    assert_eq!(
+        client.top_attendees(&0, &10),
+        vec![&env, (bob.clone(), 30), (carol.clone(), 20), (alice.clone(), 10)]
+    );
+    assert_eq!(client.top_attendees(&1, &1), vec![&env, (carol, 20)]);
+    assert_eq!(client.top_attendees(&5, &10).len(), 0);
+    assert_eq!(client.try_top_attendees(&0, &51), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_top_attendees_ranks_all_participants() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    // Participants join in ascending order of their final points
+    let users: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    for seed in 1..=4u8 {
+        let hash = session_hash(&env, seed);
+        client.set_hash(&admin, &hash);
+        for user in users.iter().skip(seed as usize - 1) {
+            client.register(user, &hash);
+        }
+    }
+
+    assert_eq!(client.top_attendees(&0, &1), vec![&env, (users[3].clone(), 40)]);
+    assert_eq!(
+        client.top_attendees(&1, &2),
+        vec![&env, (users[2].clone(), 30), (users[1].clone(), 20)]
+    );
+    assert_eq!(client.top_attendees(&3, &5), vec![&env, (users[0].clone(), 10)]);
+}
+
+#[test]
+fn test_register_signed() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    let nonce = BytesN::from_array(&env, &[7; 32]);
+    assert_eq!(client.try_register_signed(&alice, &nonce), Err(Ok(Error::NoCommitment)));
+
+    let mut preimage = Bytes::from(hash.clone());
+    preimage.append(&alice.clone().to_xdr(&env));
+    preimage.append(&Bytes::from(nonce.clone()));
+    let commitment: BytesN<32> = env.crypto().sha256(&preimage).into();
+
+    // A commitment built for another nonce does not match
+    client.submit_commitment(&alice, &commitment);
+    let other = BytesN::from_array(&env, &[8; 32]);
+    assert_eq!(client.try_register_signed(&alice, &other), Err(Ok(Error::IncorrectHash)));
+
+    client.register_signed(&alice, &nonce);
+    assert!(client.check_presence(&alice));
+
+    let bob = Address::generate(&env);
+    client.submit_commitment(&bob, &commitment);
+    assert_eq!(client.try_register_signed(&bob, &nonce), Err(Ok(Error::NonceReused)));
+}
+
+#[test]
+fn test_ttl_config() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.get_ttl_config(), AttendanceContract::default_ttl_config());
+    assert_eq!(
+        client.try_set_ttl_config(&admin, &100, &100, &200),
+        Err(Ok(Error::InvalidConfig))
+    );
+
+    // New entries start below the threshold, so the first bump applies the configured values
+    env.ledger().with_mut(|li| li.min_persistent_entry_ttl = 1);
+    client.set_ttl_config(&admin, &10, &100, &200);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    let alice = Address::generate(&env);
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        assert_eq!(storage.get_ttl(&StorageKey::ActiveHash), 100);
+        assert_eq!(storage.get_ttl(&StorageKey::UserProfile(alice.clone())), 200);
+    });
+}
+
+#[test]
+fn test_register_and_check() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    assert!(client.register_and_check(&alice, &hash));
+    assert_eq!(
+        client.try_register_and_check(&alice, &hash),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+}
+
+#[test]
+fn test_rotate_event() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &first);
+    let alice = Address::generate(&env);
+    client.register(&alice, &first);
+
+    client.set_hash(&admin, &second);
+    let rotate = (symbol_short!("rotate"), symbol_short!("v1")).into_val(&env);
+    let event = env.events().all().iter().find(|(_, topics, _)| *topics == rotate);
+    let (_, _, data) = event.unwrap();
+    let (old, new, count): (BytesN<32>, BytesN<32>, u32) = data.into_val(&env);
+    assert_eq!((old, new, count), (first, second, 1));
+}
+
+#[test]
+fn test_admin_can_attend() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+    client.register(&admin, &first);
+
+    client.set_admin_can_attend(&admin, &false);
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &second);
+    assert_eq!(client.try_register(&admin, &second), Err(Ok(Error::AdminCannotAttend)));
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &second);
+}
+
+#[test]
+fn test_require_profile() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.set_require_profile(&admin, &true);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_register(&alice, &hash), Err(Ok(Error::ProfileRequired)));
+
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.register(&alice, &hash);
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_total_checkins() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.get_total_checkins(&alice), 0);
+
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+    client.register(&alice, &first);
+
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.admin_mark_present(&admin, &alice);
+    assert_eq!(client.get_total_checkins(&alice), 2);
+
+    // Totals never reset across sessions, only withdrawn check-ins are discounted
+    client.set_hash(&admin, &session_hash(&env, 3));
+    client.admin_clear_presence(&admin, &alice, &first);
+    assert_eq!(client.get_total_checkins(&alice), 1);
+}
+
+#[test]
+fn test_admin_preregister() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &hash);
+
+    let added = client.admin_preregister(&admin, &vec![&env, alice.clone(), bob.clone()]);
+    assert_eq!(added, 1);
+    assert!(client.check_presence(&bob));
+    assert_eq!(client.get_attendance_count(&hash), 2);
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..=AttendanceContract::MAX_PREREGISTER {
+        too_many.push_back(Address::generate(&env));
+    }
+    assert_eq!(client.try_admin_preregister(&admin, &too_many), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_admin_preregister_at_cap() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    // Users with profiles also get a nickname snapshot, the costliest case
+    let mut users = Vec::new(&env);
+    let empty = String::from_str(&env, "");
+    for index in 0..AttendanceContract::MAX_PREREGISTER {
+        let user = Address::generate(&env);
+        client.set_profile(&user, &numbered_nickname(&env, b'a', index), &empty, &empty);
+        users.push_back(user);
+    }
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    assert_eq!(client.admin_preregister(&admin, &users), AttendanceContract::MAX_PREREGISTER);
+    assert!(env.cost_estimate().resources().write_entries <= MAX_WRITE_ENTRIES);
+}
+
+#[test]
+fn test_avatar_uri() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let avatar = String::from_str(&env, "ipfs://bafy-avatar");
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &avatar, &String::from_str(&env, ""));
+    assert_eq!(client.get_profile(&alice).unwrap().avatar_uri, avatar);
+
+    let long = String::from_str(&env, &"a".repeat(AttendanceContract::MAX_AVATAR_URI as usize + 1));
+    assert_eq!(
+        client.try_set_profile(&alice, &String::from_str(&env, "Satoshi"), &long, &String::from_str(&env, "")),
+        Err(Ok(Error::InvalidAvatarUri))
+    );
+
+    // Profiles stored before the avatar field existed still decode
+    let bob = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        let mut legacy: Map<Symbol, Val> = Map::new(&env);
+        legacy.set(Symbol::new(&env, "nickname"), String::from_str(&env, "Hal").into_val(&env));
+        legacy.set(Symbol::new(&env, "registered_at"), 7u32.into_val(&env));
+        env.storage().persistent().set(&StorageKey::UserProfile(bob.clone()), &legacy);
+    });
+
+    let profile = client.get_profile(&bob).unwrap();
+    assert_eq!(profile.nickname, String::from_str(&env, "Hal"));
+    assert_eq!(profile.registered_at, 7);
+    assert_eq!(profile.avatar_uri, String::from_str(&env, ""));
+    assert_eq!(profile.last_updated, 7);
+    assert_eq!(profile.registered_timestamp, 0);
+    assert_eq!(profile.bio, String::from_str(&env, ""));
+}
+
+#[test]
+fn test_admin_purge_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &first);
+    client.register(&bob, &first);
+
+    assert_eq!(client.try_admin_purge_session(&admin, &first), Err(Ok(Error::CannotPurgeActive)));
+
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.admin_purge_session(&admin, &first);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("adm_purge"), symbol_short!("v2")).into_val(&env));
+
+    // The event is only queued; the presences go with the next purge chunk
+    client.admin_purge_session(&admin, &first);
+    assert_eq!(client.purge_queue_len(), 1);
+    assert!(client.check_presence_for(&alice, &first));
+
+    assert_eq!(client.purge_evicted(&admin, &10), 2);
+    assert_eq!(client.get_attendees(&first).len(), 0);
+    assert!(!client.check_presence_for(&alice, &first));
+    assert_eq!(client.get_attendance_count(&first), 2);
+
+    // Purging again is a no-op
+    client.admin_purge_session(&admin, &first);
+    assert_eq!(client.purge_evicted(&admin, &10), 0);
+    assert_eq!(client.purge_queue_len(), 0);
+}
+
+#[test]
+fn test_registration_open() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    assert!(!client.registration_open());
+
+    env.ledger().set_sequence_number(100);
+    client.set_hash_with_expiry(&admin, &session_hash(&env, 1), &110);
+    assert!(client.registration_open());
+
+    client.set_paused(&admin, &true);
+    assert!(!client.registration_open());
+    client.set_paused(&admin, &false);
+
+    env.ledger().set_sequence_number(111);
+    assert!(!client.registration_open());
+
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.close_session(&admin);
+    assert!(!client.registration_open());
+}
+
+#[test]
+fn test_require_initialized() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_register(&alice, &session_hash(&env, 1)), Err(Ok(Error::NotInitialized)));
+    assert_eq!(
+        client.try_set_profile(&alice, &String::from_str(&env, "Satoshi"), &String::from_str(&env, ""), &String::from_str(&env, "")),
+        Err(Ok(Error::NotInitialized))
+    );
+    assert_eq!(client.try_check_presence(&alice), Err(Ok(Error::NotInitialized)));
+    assert_eq!(client.try_check_batch(&vec![&env, alice]), Err(Ok(Error::NotInitialized)));
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let successor = Address::generate(&env);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+
+    client.propose_admin(&successor);
+    client.cancel_admin_transfer();
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+    assert_eq!(client.try_cancel_admin_transfer(), Err(Ok(Error::NoPendingAdmin)));
+
+    client.propose_admin(&successor);
+    assert_eq!(client.get_admin(), admin);
+    client.accept_admin();
+    assert_eq!(client.get_admin(), successor);
+    assert!(!client.is_admin(&admin));
+
+    // The proposal is consumed by the handoff
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+}
+
+#[test]
+fn test_transfer_to_current_admin() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    // A single admin handing over to itself keeps the role
+    client.transfer_admin(&admin);
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.admin_history(), vec![&env, admin.clone()]);
+
+    // Secondary admins survive the primary re-naming itself, by either path
+    let staff = Address::generate(&env);
+    client.add_admin(&admin, &staff);
+    client.transfer_admin(&admin);
+    assert_eq!(client.get_admin(), admin);
+    assert!(client.is_admin(&staff));
+
+    client.propose_admin(&admin);
+    client.accept_admin();
+    assert_eq!(client.get_admin(), admin);
+    assert!(client.is_admin(&staff));
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+}
+
+#[test]
+fn test_profile_cooldown() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    env.ledger().set_sequence_number(100);
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &empty, &empty);
+
+    let cooldown = AttendanceContract::DEFAULT_PROFILE_COOLDOWN;
+    env.ledger().set_sequence_number(100 + cooldown - 1);
+    assert_eq!(
+        client.try_set_profile(&alice, &String::from_str(&env, "Hal"), &empty, &empty),
+        Err(Ok(Error::ProfileUpdateTooSoon))
+    );
+
+    env.ledger().set_sequence_number(100 + cooldown);
+    client.set_profile(&alice, &String::from_str(&env, "Hal"), &empty, &empty);
+
+    // Updates keep the original registration ledger
+    let profile = client.get_profile(&alice).unwrap();
+    assert_eq!(profile.registered_at, 100);
+    assert_eq!(profile.last_updated, 100 + cooldown);
+
+    client.set_profile_cooldown(&admin, &0);
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &empty, &empty);
+}
+
+#[test]
+fn test_check_batch_detailed() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let users = vec![&env, alice.clone(), bob.clone()];
+    let satoshi = String::from_str(&env, "Satoshi");
+    let empty = String::from_str(&env, "");
+    client.set_profile(&alice, &satoshi, &empty, &empty);
+
+    assert_eq!(
+        client.check_batch_detailed(&users),
+        vec![&env, (false, satoshi.clone()), (false, empty.clone())]
+    );
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&bob, &hash);
+
+    assert_eq!(client.check_batch_detailed(&users), vec![&env, (false, satoshi), (true, empty)]);
+}
+
+#[test]
+fn test_check_presence_by_nickname() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let satoshi = String::from_str(&env, "Satoshi");
+    client.set_profile(&alice, &satoshi, &String::from_str(&env, ""), &String::from_str(&env, ""));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    assert!(!client.check_presence_by_nickname(&satoshi));
+
+    client.register(&alice, &hash);
+    assert!(client.check_presence_by_nickname(&satoshi));
+    assert!(!client.check_presence_by_nickname(&String::from_str(&env, "Nobody")));
+}
+
+#[test]
+fn test_config_change_events() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.set_points_per_checkin(&admin, &25);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("cfg_chg"), symbol_short!("v1")).into_val(&env));
+    let (by, key, value): (Address, Symbol, u64) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!((by, key, value), (admin.clone(), symbol_short!("points"), 25));
+
+    // Pausing keeps its dedicated event and is also recorded as a config change
+    client.set_paused(&admin, &true);
+    let events = env.events().all();
+    let (_, topics, _) = events.get(events.len() - 2).unwrap();
+    assert_eq!(topics, (symbol_short!("paused"), symbol_short!("v1")).into_val(&env));
+    let (_, _, data) = events.last().unwrap();
+    let (_, key, value): (Address, Symbol, bool) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!((key, value), (symbol_short!("paused"), true));
+}
+
+#[test]
+fn test_is_initialized() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(&env, &contract_id);
+    assert!(!client.is_initialized());
+
+    assert_eq!(client.try_initialize(&contract_id, &None), Err(Ok(Error::InvalidAdmin)));
+    assert!(!client.is_initialized());
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin, &None);
+    assert!(client.is_initialized());
+    assert_eq!(client.try_initialize(&admin, &None), Err(Ok(Error::AlreadyInitialized)));
+}
+
+#[test]
+fn test_register_for() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    client.register_for(&admin, &alice, &hash);
+
+    // Both the sponsoring admin and the user authorized the call
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == admin));
+    assert!(auths.iter().any(|(address, _)| *address == alice));
+    assert!(client.check_presence(&alice));
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_register_for(&outsider, &alice, &hash), Err(Ok(Error::NotAdmin)));
+}
+
+#[test]
+fn test_weighted_sessions() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let workshop = session_hash(&env, 1);
+    client.set_hash_weighted(&admin, &workshop, &3);
+    assert_eq!(client.get_session_info(&workshop).unwrap().weight, 3);
+    client.register(&alice, &workshop);
+    assert_eq!(client.get_points(&alice), 30);
+
+    assert_eq!(client.try_set_hash_weighted(&admin, &session_hash(&env, 2), &0), Err(Ok(Error::InvalidWeight)));
+    assert_eq!(client.try_set_hash_weighted(&admin, &session_hash(&env, 2), &101), Err(Ok(Error::InvalidWeight)));
+
+    // Sessions stored before weights existed award the base amount
+    let legacy = session_hash(&env, 3);
+    client.set_hash(&admin, &legacy);
+    env.as_contract(&client.address, || {
+        let mut info: Map<Symbol, Val> = Map::new(&env);
+        info.set(Symbol::new(&env, "name"), String::from_str(&env, "").into_val(&env));
+        info.set(Symbol::new(&env, "created_at"), 0u32.into_val(&env));
+        info.set(Symbol::new(&env, "hash"), legacy.clone().into_val(&env));
+        env.storage().persistent().set(&StorageKey::SessionInfo(legacy.clone()), &info);
+    });
+    assert_eq!(client.get_session_info(&legacy).unwrap().weight, 1);
+    assert_eq!(client.get_session_info(&legacy).unwrap().created_by, None);
+    client.register(&alice, &legacy);
+    assert_eq!(client.get_points(&alice), 40);
+}
+
+#[test]
+fn test_session_capacity() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    assert_eq!(client.remaining_capacity(), None);
+
+    let hash = session_hash(&env, 3);
+    client.set_hash_with_capacity(&admin, &hash, &2);
+    assert_eq!(client.remaining_capacity(), Some(2));
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &hash);
+    client.register(&bob, &hash);
+    assert_eq!(client.remaining_capacity(), Some(0));
+    assert_eq!(client.try_register(&Address::generate(&env), &hash), Err(Ok(Error::SessionFull)));
+
+    // A withdrawn check-in frees a seat
+    client.deregister(&bob);
+    assert_eq!(client.remaining_capacity(), Some(1));
+
+    client.set_hash_with_capacity(&admin, &session_hash(&env, 2), &0);
+    assert_eq!(client.remaining_capacity(), None);
+}
+
+#[test]
+fn test_attendance_record() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_profile_cooldown(&admin, &0);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    let satoshi = String::from_str(&env, "Satoshi");
+    client.set_profile(&alice, &satoshi, &empty, &empty);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    env.ledger().set_sequence_number(42);
+    client.register(&alice, &hash);
+    client.register(&bob, &hash);
+
+    // Renaming later does not rewrite history
+    client.set_profile(&alice, &String::from_str(&env, "Hal"), &empty, &empty);
+    assert_eq!(client.get_attendance_record(&alice, &hash), Some((42, satoshi)));
+    assert_eq!(client.get_attendance_record(&bob, &hash), Some((42, empty)));
+    assert_eq!(client.get_attendance_record(&alice, &session_hash(&env, 2)), None);
+}
+
+#[test]
+fn test_strict_sessions() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &first);
+    client.set_strict_sessions(&admin, &true);
+
+    assert_eq!(client.try_set_hash(&admin, &second), Err(Ok(Error::SessionStillActive)));
+    assert_eq!(client.try_set_hash_weighted(&admin, &second, &2), Err(Ok(Error::SessionStillActive)));
+    assert_eq!(client.get_session(), Some(first));
+
+    client.close_session(&admin);
+    client.set_hash(&admin, &second);
+    assert_eq!(client.get_session(), Some(second));
+
+    // Without strict mode sessions can be overwritten again
+    client.set_strict_sessions(&admin, &false);
+    client.set_hash(&admin, &session_hash(&env, 3));
+}
+
+#[test]
+fn test_profile_timestamp() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_profile_cooldown(&admin, &0);
+
+    let alice = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    env.ledger().set_timestamp(1_700_000_000);
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &empty, &empty);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("profile"), symbol_short!("v3")).into_val(&env));
+    let (_, _, _, timestamp): (Address, String, String, u64) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(timestamp, 1_700_000_000);
+
+    // Updates keep the original registration time
+    env.ledger().set_timestamp(1_800_000_000);
+    client.set_profile(&alice, &String::from_str(&env, "Hal"), &empty, &empty);
+    assert_eq!(client.get_profile(&alice).unwrap().registered_timestamp, 1_700_000_000);
+}
+
+#[test]
+fn test_readonly_views() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.min_persistent_entry_ttl = 1);
+    client.set_ttl_config(&admin, &10, &100, &200);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    let alice = Address::generate(&env);
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.register(&alice, &hash);
+
+    // Let every entry fall below the threshold, then poll without extending anything
+    env.ledger().set_sequence_number(env.ledger().sequence() + 95);
+    assert_eq!(client.get_profile_readonly(&alice).unwrap().nickname, String::from_str(&env, "Satoshi"));
+    assert_eq!(client.get_session_readonly(), Some(hash.clone()));
+    assert!(client.check_presence_readonly(&alice));
+    assert_eq!(client.get_admin_readonly(), admin);
+
+    let ttl_of = |key: &StorageKey| env.as_contract(&client.address, || env.storage().persistent().get_ttl(key));
+    assert_eq!(ttl_of(&StorageKey::ActiveHash), 5);
+    assert_eq!(ttl_of(&StorageKey::Presence(hash.clone(), alice.clone())), 5);
+
+    // The regular getters still keep entries alive
+    client.get_session();
+    client.check_presence(&alice);
+    assert_eq!(ttl_of(&StorageKey::ActiveHash), 100);
+    assert_eq!(ttl_of(&StorageKey::Presence(hash, alice)), 100);
+}
+
+#[test]
+fn test_claim_session_reward() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &first);
+
+    assert_eq!(client.claim_session_reward(&alice, &None), 15);
+    assert_eq!(client.try_claim_session_reward(&alice, &None), Err(Ok(Error::AlreadyClaimed)));
+    assert_eq!(client.try_claim_session_reward(&bob, &None), Err(Ok(Error::NotRegistered)));
+
+    // Closed sessions can still be claimed by naming them
+    client.register(&bob, &first);
+    client.close_session(&admin);
+    assert_eq!(client.try_claim_session_reward(&bob, &None), Err(Ok(Error::NoActiveSession)));
+    client.set_session_reward(&admin, &50);
+    assert_eq!(client.claim_session_reward(&bob, &Some(first)), 60);
+}
+
+#[test]
+fn test_token_reward() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    let token = soroban_sdk::token::TokenClient::new(&env, &sac.address());
+    soroban_sdk::token::StellarAssetClient::new(&env, &sac.address()).mint(&client.address, &150);
+
+    client.set_reward_token(&admin, &sac.address(), &100, &false);
+    assert_eq!(client.try_set_reward_token(&admin, &sac.address(), &-1, &false), Err(Ok(Error::InvalidConfig)));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &hash);
+    assert_eq!(token.balance(&alice), 100);
+
+    // The contract ran dry: presence is still recorded and the failure is reported
+    let bob = Address::generate(&env);
+    client.register(&bob, &hash);
+    let failed = env.events().all().iter().any(|(_, topics, _)| {
+        topics == (Symbol::new(&env, "reward_fail"), symbol_short!("v1")).into_val(&env)
+    });
+    assert!(failed);
+    assert!(client.check_presence(&bob));
+    assert_eq!(token.balance(&bob), 0);
+
+    client.set_reward_token(&admin, &sac.address(), &100, &true);
+    let carol = Address::generate(&env);
+    assert_eq!(client.try_register(&carol, &hash), Err(Ok(Error::RewardTransferFailed)));
+    assert!(!client.check_presence(&carol));
+
+    // Withdrawing and checking in again does not pay twice, even with funds available
+    soroban_sdk::token::StellarAssetClient::new(&env, &sac.address()).mint(&client.address, &100);
+    client.deregister(&alice);
+    client.register(&alice, &hash);
+    assert_eq!(token.balance(&alice), 100);
+    assert_eq!(token.balance(&client.address), 150);
+}
+
+#[test]
+fn test_batch_reverted_reward_writes_nothing() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    client.set_reward_token(&admin, &sac.address(), &100, &true);
+    client.set_points_per_checkin(&admin, &10);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    // The contract holds no funds, so the payout reverts before the check-in is written
+    let alice = Address::generate(&env);
+    let results = client.register_batch(&vec![&env, (alice.clone(), hash.clone())]);
+
+    assert_eq!(results, vec![&env, Err(Error::RewardTransferFailed)]);
+    assert!(!client.check_presence(&alice));
+    assert_eq!(client.get_attendance_count(&hash), 0);
+    assert_eq!(client.get_points(&alice), 0);
+}
+
+#[test]
+fn test_badges() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let nft = env.register(MockBadge, ());
+    client.set_badge_contract(&admin, &nft);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &hash);
+    assert!(client.has_badge(&alice, &hash));
+
+    // A failing mint does not block the check-in
+    MockBadgeClient::new(&env, &nft).set_failing(&true);
+    let bob = Address::generate(&env);
+    client.register(&bob, &hash);
+    let failed = env.events().all().iter().any(|(_, topics, _)| {
+        topics == (Symbol::new(&env, "badge_fail"), symbol_short!("v1")).into_val(&env)
+    });
+    assert!(failed);
+    assert!(client.check_presence(&bob));
+    assert!(!client.has_badge(&bob, &hash));
+}
+
+#[test]
+fn test_reregister_mints_badge_once() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let nft = env.register(MockBadge, ());
+    client.set_badge_contract(&admin, &nft);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &hash);
+    for _ in 0..5 {
+        client.deregister(&alice);
+        client.register(&alice, &hash);
+    }
+    assert_eq!(MockBadgeClient::new(&env, &nft).minted(), 1);
+    assert!(client.has_badge(&alice, &hash));
+}
+
+#[test]
+fn test_upgrade_requires_admin() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_upgrade(&outsider, &BytesN::from_array(&env, &[7; 32])),
+        Err(Ok(Error::NotAdmin))
+    );
+}
+
+#[test]
+fn test_session_count() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.session_count(), 0);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.set_hash_named(&admin, &session_hash(&env, 2), &String::from_str(&env, "Workshop"));
+
+    let (_, _, data) = env.events().all().last().unwrap();
+    let (hash, index, _memo): (BytesN<32>, u32, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!((hash, index), (session_hash(&env, 2), 2));
+    assert_eq!(client.session_count(), 2);
+}
+
+#[test]
+fn test_nickname_characters() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    for bad in ["Sato\nshi", "Sato\0shi", "Satoshi\t", " Satoshi", "Satoshi ", "Sato\u{7f}shi"] {
+        assert_eq!(
+            client.try_set_profile(&alice, &String::from_str(&env, bad), &empty, &empty),
+            Err(Ok(Error::InvalidNickname))
+        );
+    }
+
+    // Inner spaces and non-ASCII letters are fine
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi Nakamoto"), &empty, &empty);
+    client.set_profile_cooldown(&admin, &0);
+    client.set_profile(&alice, &String::from_str(&env, "João"), &empty, &empty);
+}
+
+#[test]
+fn test_default_session_duration() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    env.ledger().set_sequence_number(100);
+    client.set_default_session_duration(&admin, &10);
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    env.ledger().set_sequence_number(110);
+    client.register(&Address::generate(&env), &hash);
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.try_register(&Address::generate(&env), &hash), Err(Ok(Error::SessionExpired)));
+
+    // An explicit expiry wins over the default
+    let second = session_hash(&env, 2);
+    client.set_hash_with_expiry(&admin, &second, &200);
+    env.ledger().set_sequence_number(150);
+    client.register(&Address::generate(&env), &second);
+
+    client.set_default_session_duration(&admin, &0);
+    let third = session_hash(&env, 3);
+    client.set_hash(&admin, &third);
+    env.ledger().set_sequence_number(1_000);
+    client.register(&Address::generate(&env), &third);
+}
+
+#[test]
+fn test_session_state() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let state = client.session_state();
+    assert_eq!(state.active_hash, None);
+    assert!(!state.open);
+
+    env.ledger().set_sequence_number(100);
+    let hash = session_hash(&env, 1);
+    client.set_hash_with_expiry(&admin, &hash, &120);
+    client.register(&Address::generate(&env), &hash);
+    client.set_paused(&admin, &true);
+
+    assert_eq!(
+        client.session_state(),
+        SessionState {
+            active_hash: Some(hash),
+            paused: true,
+            attendee_count: 1,
+            expires_at: Some(120),
+            open: false,
+        }
+    );
+
+    client.set_paused(&admin, &false);
+    assert!(client.session_state().open);
+}
+
+#[test]
+fn test_presence_record() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    env.ledger().set_sequence_number(42);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &hash);
+    client.admin_mark_present(&admin, &bob);
+
+    assert_eq!(
+        client.get_presence_record(&alice, &hash),
+        Some(PresenceRecord { at: 42, marked_by: None })
+    );
+    assert_eq!(
+        client.get_presence_record(&bob, &hash),
+        Some(PresenceRecord { at: 42, marked_by: Some(admin) })
+    );
+
+    // Older entries stored the bare ledger or `true`
+    let carol = Address::generate(&env);
+    let dave = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        env.storage().persistent().set(&StorageKey::Presence(hash.clone(), carol.clone()), &7u32);
+        env.storage().persistent().set(&StorageKey::Presence(hash.clone(), dave.clone()), &true);
+    });
+    assert_eq!(client.get_presence_record(&carol, &hash), Some(PresenceRecord { at: 7, marked_by: None }));
+    assert_eq!(client.get_presence_record(&dave, &hash), Some(PresenceRecord { at: 0, marked_by: None }));
+    assert_eq!(client.get_checkin_time(&carol), Some(7));
+    assert_eq!(client.get_checkin_time(&dave), None);
+}
+
+#[test]
+fn test_deregister_after_expiry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    env.ledger().set_sequence_number(100);
+    client.set_hash_with_expiry(&admin, &hash, &110);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &hash);
+    client.register(&bob, &hash);
+
+    env.ledger().set_sequence_number(110);
+    client.deregister(&alice);
+    assert!(!client.check_presence(&alice));
+
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.try_deregister(&bob), Err(Ok(Error::SessionExpired)));
+    assert!(client.check_presence(&bob));
+}
+
+#[test]
+fn test_attendees_page() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    let mut users = Vec::new(&env);
+    for _ in 0..5 {
+        let user = Address::generate(&env);
+        client.register(&user, &hash);
+        users.push_back(user);
+    }
+
+    assert_eq!(client.get_attendees_page(&hash, &0, &2), users.slice(0..2));
+    assert_eq!(client.get_attendees_page(&hash, &4, &2), users.slice(4..5));
+    assert_eq!(client.get_attendees_page(&hash, &9, &2).len(), 0);
+    assert_eq!(client.get_attendees_page(&session_hash(&env, 2), &0, &2).len(), 0);
+    assert_eq!(client.try_get_attendees_page(&hash, &0, &101), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_close_summary_event() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    env.ledger().set_sequence_number(100);
+    client.set_hash(&admin, &hash);
+    client.register(&Address::generate(&env), &hash);
+    client.register(&Address::generate(&env), &hash);
+
+    env.ledger().set_sequence_number(160);
+    client.close_session(&admin);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("summary"), symbol_short!("v1")).into_val(&env));
+    let summary: (BytesN<32>, u32, u32, u32) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(summary, (hash, 2, 100, 160));
+}
+
+#[test]
+fn test_moderators() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let moderator = Address::generate(&env);
+    let alice = Address::generate(&env);
+    assert_eq!(client.try_set_hash(&moderator, &session_hash(&env, 1)), Err(Ok(Error::NotAdmin)));
+
+    client.add_moderator(&admin, &moderator);
+    assert!(client.is_moderator(&moderator));
+    assert!(!client.is_admin(&moderator));
+
+    // Moderators run sessions...
+    client.set_hash(&moderator, &session_hash(&env, 1));
+    client.admin_mark_present(&moderator, &alice);
+    assert_eq!(
+        client.get_presence_record(&alice, &session_hash(&env, 1)).unwrap().marked_by,
+        Some(moderator.clone())
+    );
+    client.close_session(&moderator);
+
+    // ...but cannot manage roles, settings or upgrades
+    let other = Address::generate(&env);
+    assert_eq!(client.try_add_moderator(&moderator, &other), Err(Ok(Error::NotAdmin)));
+    assert_eq!(client.try_add_admin(&moderator, &other), Err(Ok(Error::NotAdmin)));
+    assert_eq!(client.try_set_paused(&moderator, &true), Err(Ok(Error::NotAdmin)));
+    assert_eq!(
+        client.try_upgrade(&moderator, &BytesN::from_array(&env, &[7; 32])),
+        Err(Ok(Error::NotAdmin))
+    );
+
+    client.remove_moderator(&admin, &moderator);
+    assert!(!client.is_moderator(&moderator));
+    assert_eq!(client.try_remove_moderator(&admin, &moderator), Err(Ok(Error::NotModerator)));
+    assert_eq!(client.try_set_hash(&moderator, &session_hash(&env, 2)), Err(Ok(Error::NotAdmin)));
+}
+
+#[test]
+fn test_zero_hash_rejected() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let zero = BytesN::from_array(&env, &[0; 32]);
+    assert_eq!(client.try_set_hash(&admin, &zero), Err(Ok(Error::InvalidHash)));
+    assert_eq!(client.try_set_hash_with_expiry(&admin, &zero, &100), Err(Ok(Error::InvalidHash)));
+    assert_eq!(client.get_session(), None);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    assert_eq!(client.try_register(&Address::generate(&env), &zero), Err(Ok(Error::IncorrectHash)));
+}
+
+#[test]
+fn test_hash_rotation_within_event() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let event_id = session_hash(&env, 9);
+    let first = session_hash(&env, 1);
+    let second = session_hash(&env, 2);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    client.set_hash_for_event(&admin, &first, &event_id);
+    client.register(&alice, &first);
+
+    // Rotating the code keeps earlier check-ins and the session index
+    client.set_hash_for_event(&admin, &second, &event_id);
+    assert_eq!(client.session_count(), 1);
+    assert!(client.check_presence(&alice));
+    assert_eq!(client.try_register(&alice, &second), Err(Ok(Error::AlreadyRegistered)));
+    assert_eq!(client.try_register(&bob, &first), Err(Ok(Error::IncorrectHash)));
+
+    client.register(&bob, &second);
+    assert_eq!(client.get_session_info(&second).unwrap().event_id, event_id);
+    assert_eq!(client.get_attendance_count(&first), 2);
+    assert_eq!(client.get_attendees(&second), vec![&env, alice.clone(), bob.clone()]);
+    assert!(client.check_presence_for(&bob, &first));
+
+    // A different event starts a fresh session
+    client.set_hash(&admin, &session_hash(&env, 3));
+    assert_eq!(client.session_count(), 2);
+    assert!(!client.check_presence(&alice));
+    assert_eq!(client.get_session_info(&session_hash(&env, 3)).unwrap().event_id, session_hash(&env, 3));
+}
+
+#[test]
+fn test_error_message() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    assert_eq!(client.error_message(&4), String::from_str(&env, "Incorrect hash"));
+    assert_eq!(
+        client.error_message(&(Error::SessionFull as u32)),
+        String::from_str(&env, "Session is full")
+    );
+    assert_eq!(client.error_message(&0), String::from_str(&env, "Unknown error"));
+    assert_eq!(client.error_message(&9999), String::from_str(&env, "Unknown error"));
+}
+
+#[test]
+fn test_profile_requires_attendance() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let nickname = String::from_str(&env, "alice");
+    let avatar = String::from_str(&env, "");
+
+    client.set_profile_requires_attendance(&admin, &true);
+    assert_eq!(client.try_set_profile(&alice, &nickname, &avatar, &String::from_str(&env, "")), Err(Ok(Error::MustAttendFirst)));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&alice, &hash);
+    client.set_profile(&alice, &nickname, &avatar, &String::from_str(&env, ""));
+    assert_eq!(client.get_profile(&alice).unwrap().nickname, nickname);
+
+    client.set_profile_requires_attendance(&admin, &false);
+    client.set_profile(&Address::generate(&env), &String::from_str(&env, "bob"), &avatar, &String::from_str(&env, ""));
+}
+
+#[test]
+fn test_session_memo() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    let memo = String::from_str(&env, "Room 2, Soroban workshop");
+    client.set_hash_with_memo(&admin, &hash, &memo);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("new_sess"), symbol_short!("v3")).into_val(&env));
+    let payload: (BytesN<32>, u32, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (hash.clone(), 1, memo.clone()));
+
+    assert_eq!(client.get_session_info(&hash).unwrap().memo, memo);
+
+    let long_memo = String::from_str(&env, &"m".repeat(65));
+    assert_eq!(
+        client.try_set_hash_with_memo(&admin, &session_hash(&env, 2), &long_memo),
+        Err(Ok(Error::InvalidMemo))
+    );
+
+    client.set_hash(&admin, &session_hash(&env, 3));
+    assert_eq!(client.get_session_info(&session_hash(&env, 3)).unwrap().memo, String::from_str(&env, ""));
+}
+
+#[test]
+fn test_daily_count() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 3 * 86_400 + 60);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&Address::generate(&env), &hash);
+    client.admin_mark_present(&admin, &Address::generate(&env));
+    assert_eq!(client.get_daily_count(&3), 2);
+
+    env.ledger().with_mut(|li| li.timestamp = 4 * 86_400);
+    client.register(&Address::generate(&env), &hash);
+    assert_eq!(client.get_daily_count(&3), 2);
+    assert_eq!(client.get_daily_count(&4), 1);
+    assert_eq!(client.get_daily_count(&5), 0);
+
+    // Withdrawing and checking in again does not inflate the count
+    let alice = Address::generate(&env);
+    client.register(&alice, &hash);
+    for _ in 0..5 {
+        client.deregister(&alice);
+        client.register(&alice, &hash);
+    }
+    assert_eq!(client.get_daily_count(&4), 2);
+}
+
+#[test]
+fn test_my_attendance() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    for seed in 1..=3 {
+        client.set_hash(&admin, &session_hash(&env, seed));
+        if seed != 2 {
+            client.register(&alice, &session_hash(&env, seed));
+        }
+    }
+
+    let hashes = vec![&env, session_hash(&env, 1), session_hash(&env, 2), session_hash(&env, 3), session_hash(&env, 4)];
+    assert_eq!(client.my_attendance(&alice, &hashes), vec![&env, true, false, true, false]);
+
+    let mut too_many = Vec::new(&env);
+    for seed in 0..51 {
+        too_many.push_back(session_hash(&env, seed));
+    }
+    assert_eq!(client.try_my_attendance(&alice, &too_many), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_first_checkin_event() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let first = session_hash(&env, 1);
+    client.set_hash(&admin, &first);
+    client.register(&alice, &first);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("first_in"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (alice.clone(), first.clone()));
+
+    // Withdrawing and checking in again is not a debut, nor is any later session
+    client.deregister(&alice);
+    client.register(&alice, &first);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("present"), symbol_short!("v4")).into_val(&env));
+
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &second);
+    client.register(&alice, &second);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("present"), symbol_short!("v4")).into_val(&env));
+}
+
+#[test]
+fn test_first_checkin_event_admin_marked() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    // An admin mark is the debut, so `first_in` follows `adm_mark`
+    let alice = Address::generate(&env);
+    client.admin_mark_present(&admin, &alice);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("first_in"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (alice.clone(), hash.clone()));
+
+    // Self check-ins after the mark are no longer a debut
+    let second = session_hash(&env, 2);
+    client.set_hash(&admin, &second);
+    client.register(&alice, &second);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("present"), symbol_short!("v4")).into_val(&env));
+
+    // Pre-registered users get one too, and a marked opted-out user stays anonymous
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    client.set_opt_out(&carol, &true);
+    client.admin_preregister(&admin, &vec![&env, bob.clone(), carol.clone()]);
+    let events = env.events().all();
+    let anonymous = Address::from_str(&env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF");
+    for (index, expected) in [(events.len() - 3, bob), (events.len() - 1, anonymous)] {
+        let (_, topics, data) = events.get(index).unwrap();
+        assert_eq!(topics, (symbol_short!("first_in"), symbol_short!("v1")).into_val(&env));
+        let payload: (Address, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+        assert_eq!(payload, (expected, second.clone()));
+    }
+}
+
+#[test]
+fn test_admin_set_nickname() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let avatar = String::from_str(&env, "ipfs://alice");
+    let abusive = String::from_str(&env, "abusive");
+    client.set_profile(&alice, &abusive, &avatar, &String::from_str(&env, ""));
+
+    let renamed = String::from_str(&env, "ambassador1");
+    client.admin_set_nickname(&admin, &alice, &renamed);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "adm_rename"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, Address, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), alice.clone(), renamed.clone()));
+
+    let profile = client.get_profile(&alice).unwrap();
+    assert_eq!(profile.nickname, renamed);
+    assert_eq!(profile.avatar_uri, avatar);
+    assert_eq!(client.resolve_nickname(&abusive), None);
+    assert_eq!(client.resolve_nickname(&renamed), Some(alice.clone()));
+    assert_eq!(client.try_set_profile(&alice, &abusive, &avatar, &String::from_str(&env, "")), Err(Ok(Error::ProfileUpdateTooSoon)));
+
+    // Users without a profile get one; nicknames are validated and must be free
+    let bob = Address::generate(&env);
+    assert_eq!(
+        client.try_admin_set_nickname(&admin, &bob, &String::from_str(&env, "x")),
+        Err(Ok(Error::InvalidNickname))
+    );
+    assert_eq!(client.try_admin_set_nickname(&admin, &bob, &renamed), Err(Ok(Error::NicknameTaken)));
+    client.admin_set_nickname(&admin, &bob, &String::from_str(&env, "bobby"));
+    assert_eq!(client.get_profile(&bob).unwrap().avatar_uri, String::from_str(&env, ""));
+
+    assert_eq!(
+        client.try_admin_set_nickname(&Address::generate(&env), &bob, &String::from_str(&env, "robert")),
+        Err(Ok(Error::NotAdmin))
+    );
+}
+
+#[test]
+fn test_session_creator() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let moderator = Address::generate(&env);
+    client.add_moderator(&admin, &moderator);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.set_hash_named(&moderator, &session_hash(&env, 2), &String::from_str(&env, "Meetup"));
+
+    assert_eq!(client.get_session_info(&session_hash(&env, 1)).unwrap().created_by, Some(admin));
+    assert_eq!(client.get_session_info(&session_hash(&env, 2)).unwrap().created_by, Some(moderator));
+}
+
+#[test]
+fn test_hash_reuse_rejected() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&alice, &hash);
+    client.set_hash(&admin, &session_hash(&env, 2));
+
+    assert_eq!(client.try_set_hash(&admin, &hash), Err(Ok(Error::HashAlreadyUsed)));
+    assert_eq!(client.try_set_hash_force(&admin, &hash, &false), Err(Ok(Error::HashAlreadyUsed)));
+    assert_eq!(
+        client.try_set_hash_for_event(&admin, &session_hash(&env, 2), &session_hash(&env, 2)),
+        Err(Ok(Error::HashAlreadyUsed))
+    );
+
+    // Intentional reuse shares the earlier presence entries
+    client.set_hash_force(&admin, &hash, &true);
+    assert_eq!(client.get_session(), Some(hash.clone()));
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_session_ttl() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.session_ttl(), None);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    let config = client.get_ttl_config();
+    let ttl = client.session_ttl().unwrap();
+    assert_eq!(ttl, config.session_bump);
+    let actual = env.as_contract(&client.address, || env.storage().persistent().get_ttl(&StorageKey::ActiveHash));
+    assert!(actual >= ttl);
+
+    // Reads above the threshold write nothing, not even the tracked bound
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    client.check_presence(&Address::generate(&env));
+    assert_eq!(env.cost_estimate().resources().write_entries, 0);
+    assert_eq!(client.session_ttl(), Some(ttl - 100));
+
+    // Activity extends the guaranteed lifetime again once it runs low
+    env.ledger().with_mut(|li| li.sequence_number += config.session_bump - config.threshold);
+    client.register(&Address::generate(&env), &hash);
+    assert_eq!(client.session_ttl(), Some(ttl));
+
+    client.close_session(&admin);
+    assert_eq!(client.session_ttl(), None);
+}
+
+#[test]
+fn test_checkin_cooldown() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.set_checkin_cooldown(&admin, &10);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+
+    client.set_hash(&admin, &session_hash(&env, 2));
+    assert_eq!(client.try_register(&alice, &session_hash(&env, 2)), Err(Ok(Error::CheckinTooSoon)));
+    // The cooldown is per user
+    client.register(&Address::generate(&env), &session_hash(&env, 2));
+
+    env.ledger().with_mut(|li| li.sequence_number += 10);
+    client.register(&alice, &session_hash(&env, 2));
+
+    client.set_checkin_cooldown(&admin, &0);
+    client.set_hash(&admin, &session_hash(&env, 3));
+    client.register(&alice, &session_hash(&env, 3));
+}
+
+#[test]
+fn test_migrate_profile() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let nickname = String::from_str(&env, "satoshi");
+    client.set_profile(&old, &nickname, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&old, &session_hash(&env, 1));
+
+    assert_eq!(client.try_migrate_profile(&new, &old), Err(Ok(Error::ProfileNotFound)));
+    assert_eq!(client.try_migrate_profile(&old, &new), Err(Ok(Error::CheckedInActiveEvent)));
+
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.migrate_profile(&old, &new);
+    assert!(env.auths().iter().any(|(address, _)| *address == old));
+    assert!(env.auths().iter().any(|(address, _)| *address == new));
+
+    assert_eq!(client.get_profile(&old), None);
+    assert_eq!(client.get_profile(&new).unwrap().nickname, nickname);
+    assert_eq!(client.resolve_nickname(&nickname), Some(new.clone()));
+    assert_eq!(client.get_total_checkins(&new), 1);
+    assert_eq!(client.get_points(&new), 10);
+    assert_eq!(client.get_points(&old), 0);
+    assert_eq!(client.get_streak(&new), 1);
+    assert_eq!(client.top_attendees(&0, &10), vec![&env, (new.clone(), 10)]);
+
+    // The streak carries over to the next session
+    client.register(&new, &session_hash(&env, 2));
+    assert_eq!(client.get_streak(&new), 2);
+
+    let other = Address::generate(&env);
+    client.set_profile(&other, &String::from_str(&env, "other"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert_eq!(client.try_migrate_profile(&new, &other), Err(Ok(Error::ProfileAlreadyExists)));
+}
+
+#[test]
+fn test_migrated_user_cannot_reearn() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_checkin_cooldown(&admin, &1000);
+
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    client.set_profile(&old, &String::from_str(&env, "satoshi"), &empty, &empty);
+    client.set_opt_out(&old, &true);
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&old, &hash);
+
+    // Withdrawing first does not open a way around the refusal
+    client.deregister(&old);
+    assert_eq!(client.try_migrate_profile(&old, &new), Err(Ok(Error::CheckedInActiveEvent)));
+
+    // Once the event is over the profile moves, bringing the cooldown and opt-out along
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.migrate_profile(&old, &new);
+    assert_eq!(client.try_register(&new, &session_hash(&env, 2)), Err(Ok(Error::CheckinTooSoon)));
+    assert!(client.is_opted_out(&new));
+    assert!(!client.is_opted_out(&old));
+    assert_eq!(client.get_points(&new), 10);
+}
+
+#[test]
+fn test_session_exists() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let first = session_hash(&env, 1);
+    assert!(!client.session_exists(&first));
+
+    client.set_hash(&admin, &first);
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.close_session(&admin);
+
+    assert!(client.session_exists(&first));
+    assert!(client.session_exists(&session_hash(&env, 2)));
+    assert!(!client.session_exists(&session_hash(&env, 3)));
+}
+
+#[test]
+fn test_admin_set_profiles() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let entries = vec![
+        &env,
+        (alice.clone(), String::from_str(&env, "alice")),
+        (bob.clone(), String::from_str(&env, "x")),
+        (carol.clone(), String::from_str(&env, "alice")),
+    ];
+
+    let results = client.admin_set_profiles(&admin, &entries);
+    assert_eq!(results, vec![&env, Ok(()), Err(Error::InvalidNickname), Err(Error::NicknameTaken)]);
+    assert_eq!(client.get_profile(&alice).unwrap().nickname, String::from_str(&env, "alice"));
+    assert_eq!(client.get_profile(&bob), None);
+    assert_eq!(client.get_profile(&carol), None);
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..=AttendanceContract::MAX_PROFILE_BATCH {
+        too_many.push_back((Address::generate(&env), String::from_str(&env, "cohort")));
+    }
+    assert_eq!(client.try_admin_set_profiles(&admin, &too_many), Err(Ok(Error::LimitTooLarge)));
+}
+
+#[test]
+fn test_admin_set_profiles_at_cap() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let mut entries = Vec::new(&env);
+    for index in 0..AttendanceContract::MAX_PROFILE_BATCH {
+        entries.push_back((Address::generate(&env), numbered_nickname(&env, b'c', index)));
+    }
+    client.admin_set_profiles(&admin, &entries);
+
+    // Renaming every profile also releases the old nicknames, the costliest case
+    let mut renames = Vec::new(&env);
+    for (index, (user, _)) in entries.iter().enumerate() {
+        renames.push_back((user, numbered_nickname(&env, b'd', index as u32)));
+    }
+    let results = client.admin_set_profiles(&admin, &renames);
+    assert!(env.cost_estimate().resources().write_entries <= MAX_WRITE_ENTRIES);
+    assert!(results.iter().all(|result| result.is_ok()));
+}
+
+#[test]
+fn test_points_decay() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.set_points_per_checkin(&admin, &1000);
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+
+    // Without a configured rate nothing changes
+    env.ledger().with_mut(|li| li.sequence_number += 500);
+    assert_eq!(client.apply_decay(&alice), 1000);
+
+    assert_eq!(client.try_set_decay_rate(&admin, &101, &100), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.try_set_decay_rate(&admin, &10, &0), Err(Ok(Error::InvalidConfig)));
+    client.set_decay_rate(&admin, &10, &200);
+
+    // 500 ledgers of inactivity are two full periods
+    assert_eq!(client.apply_decay(&alice), 810);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("decay"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, u64, u64) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (alice.clone(), 190, 810));
+
+    // Idempotent within a period; the partial period still counts later
+    assert_eq!(client.apply_decay(&alice), 810);
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    assert_eq!(client.apply_decay(&alice), 729);
+    assert_eq!(client.get_points(&alice), 729);
+
+    // A fresh check-in restarts the inactivity clock
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.register(&alice, &session_hash(&env, 2));
+    env.ledger().with_mut(|li| li.sequence_number += 199);
+    assert_eq!(client.apply_decay(&alice), 1729);
+
+    assert_eq!(client.apply_decay(&Address::generate(&env)), 0);
+}
+
+#[test]
+fn test_can_register() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let hash = session_hash(&env, 1);
+    assert_eq!(client.try_can_register(&alice, &hash), Err(Ok(Error::NoActiveSession)));
+
+    client.set_hash_with_capacity(&admin, &hash, &1);
+    assert_eq!(client.try_can_register(&alice, &session_hash(&env, 2)), Err(Ok(Error::IncorrectHash)));
+    client.can_register(&alice, &hash);
+    // Preflighting neither needs auth nor records anything
+    assert!(env.auths().is_empty());
+    assert!(!client.check_presence(&alice));
+
+    client.register(&alice, &hash);
+    assert_eq!(client.try_can_register(&alice, &hash), Err(Ok(Error::AlreadyRegistered)));
+    assert_eq!(client.try_can_register(&Address::generate(&env), &hash), Err(Ok(Error::SessionFull)));
+
+    client.set_paused(&admin, &true);
+    assert_eq!(client.try_can_register(&Address::generate(&env), &hash), Err(Ok(Error::ContractPaused)));
+}
+
+#[test]
+fn test_admin_events_carry_nickname() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let nickname = String::from_str(&env, "alice");
+    client.set_profile(&alice, &nickname, &String::from_str(&env, ""), &String::from_str(&env, ""));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.admin_mark_present(&admin, &alice);
+    // A debut mark is followed by `first_in`
+    let events = env.events().all();
+    let (_, topics, data) = events.get(events.len() - 2).unwrap();
+    assert_eq!(topics, (symbol_short!("adm_mark"), symbol_short!("v2")).into_val(&env));
+    let payload: (Address, Address, BytesN<32>, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), alice.clone(), hash.clone(), nickname.clone()));
+
+    client.admin_clear_presence(&admin, &alice, &hash);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("adm_clr"), symbol_short!("v2")).into_val(&env));
+    let payload: (Address, Address, BytesN<32>, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), alice.clone(), hash.clone(), nickname));
+
+    // Users without a profile are reported with an empty nickname
+    let bob = Address::generate(&env);
+    client.admin_mark_present(&admin, &bob);
+    let events = env.events().all();
+    let (_, _, data) = events.get(events.len() - 2).unwrap();
+    let payload: (Address, Address, BytesN<32>, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload.3, String::from_str(&env, ""));
+}
+
+#[test]
+fn test_max_sessions_retained() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.try_set_max_sessions_retained(&admin, &0), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.try_set_max_sessions_retained(&admin, &101), Err(Ok(Error::InvalidConfig)));
+    client.set_max_sessions_retained(&admin, &2);
+
+    let alice = Address::generate(&env);
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.set_hash(&admin, &session_hash(&env, 3));
+
+    let (_, topics, data) = env.events().all().get(1).unwrap();
+    assert_eq!(topics, (symbol_short!("evict"), symbol_short!("v2")).into_val(&env));
+    let payload: (BytesN<32>, bool) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (session_hash(&env, 1), true));
+
+    assert_eq!(client.list_sessions(&false), vec![&env, session_hash(&env, 2), session_hash(&env, 3)]);
+    assert_eq!(client.purge_queue_len(), 1);
+
+    // The attendance data stays until the admin purges the queue
+    assert!(client.check_presence_for(&alice, &session_hash(&env, 1)));
+    assert_eq!(client.purge_evicted(&admin, &10), 1);
+    assert_eq!(client.purge_queue_len(), 0);
+    assert!(!client.check_presence_for(&alice, &session_hash(&env, 1)));
+    assert_eq!(client.get_attendees(&session_hash(&env, 1)), Vec::new(&env));
+
+    // An evicted code whose event is still retained keeps the attendance
+    let event_id = session_hash(&env, 9);
+    client.set_hash_for_event(&admin, &session_hash(&env, 4), &event_id);
+    client.register(&alice, &session_hash(&env, 4));
+    client.set_hash_for_event(&admin, &session_hash(&env, 5), &event_id);
+    client.set_hash_for_event(&admin, &session_hash(&env, 6), &event_id);
+    assert_eq!(client.purge_evicted(&admin, &10), 0);
+    assert_eq!(client.purge_queue_len(), 0);
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_eviction_is_bounded() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    for _ in 0..12 {
+        client.register(&Address::generate(&env), &session_hash(&env, 1));
+    }
+    for seed in 2..=8 {
+        client.set_hash(&admin, &session_hash(&env, seed));
+    }
+
+    // Lowering the limit evicts at most 5 sessions per start
+    client.set_max_sessions_retained(&admin, &1);
+    client.set_hash(&admin, &session_hash(&env, 9));
+    assert_eq!(client.list_sessions(&false).len(), 4);
+    assert_eq!(client.purge_queue_len(), 5);
+
+    // Purging works through the attendees in chunks of at most 10
+    assert_eq!(client.purge_evicted(&admin, &100), 10);
+    assert_eq!(client.purge_queue_len(), 5);
+    assert_eq!(client.purge_evicted(&admin, &100), 2);
+    assert_eq!(client.purge_queue_len(), 0);
+    assert_eq!(client.get_attendees(&session_hash(&env, 1)), Vec::new(&env));
+
+    assert_eq!(client.try_purge_evicted(&Address::generate(&env), &10), Err(Ok(Error::NotAdmin)));
+}
+
+#[test]
+fn test_attended_between() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    env.ledger().set_sequence_number(100);
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+
+    env.ledger().set_sequence_number(200);
+    client.set_hash(&admin, &session_hash(&env, 2));
+
+    assert!(client.attended_between(&alice, &50, &150));
+    assert!(client.attended_between(&alice, &100, &100));
+    assert!(!client.attended_between(&alice, &101, &300));
+    assert!(!client.attended_between(&alice, &150, &50));
+    assert!(!client.attended_between(&Address::generate(&env), &0, &u32::MAX));
+}
+
+#[test]
+fn test_total_unique_users() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    assert_eq!(client.total_unique_users(), 0);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+    client.register(&bob, &session_hash(&env, 1));
+    assert_eq!(client.total_unique_users(), 2);
+
+    // Later sessions and withdrawn check-ins don't count the same address twice
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.register(&alice, &session_hash(&env, 2));
+    client.deregister(&alice);
+    client.register(&alice, &session_hash(&env, 2));
+    assert_eq!(client.total_unique_users(), 2);
+
+    // Merging two counted addresses leaves one user
+    client.set_profile(&bob, &String::from_str(&env, "bob"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+    client.migrate_profile(&bob, &alice);
+    assert_eq!(client.total_unique_users(), 1);
+}
+
+#[test]
+fn test_withdraw_rewards() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let treasury = Address::generate(&env);
+    assert_eq!(client.try_withdraw_rewards(&admin, &treasury, &10), Err(Ok(Error::NoRewardToken)));
+
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    let token = soroban_sdk::token::TokenClient::new(&env, &sac.address());
+    soroban_sdk::token::StellarAssetClient::new(&env, &sac.address()).mint(&client.address, &150);
+    client.set_reward_token(&admin, &sac.address(), &100, &false);
+
+    assert_eq!(client.try_withdraw_rewards(&admin, &treasury, &0), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.try_withdraw_rewards(&admin, &treasury, &151), Err(Ok(Error::RewardTransferFailed)));
+
+    client.withdraw_rewards(&admin, &treasury, &120);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("withdraw"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, Address, Address, i128) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), treasury.clone(), sac.address(), 120));
+
+    assert_eq!(token.balance(&treasury), 120);
+    assert_eq!(token.balance(&client.address), 30);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(client.try_withdraw_rewards(&stranger, &treasury, &10), Err(Ok(Error::NotAdmin)));
+}
+
+#[test]
+fn test_archive_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let mistake = session_hash(&env, 1);
+    client.set_hash(&admin, &mistake);
+    client.register(&alice, &mistake);
+    client.set_hash(&admin, &session_hash(&env, 2));
+
+    assert_eq!(client.try_archive_session(&admin, &session_hash(&env, 3)), Err(Ok(Error::IncorrectHash)));
+
+    client.archive_session(&admin, &mistake);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("archive"), symbol_short!("v1")).into_val(&env));
+
+    assert_eq!(client.list_sessions(&false), vec![&env, session_hash(&env, 2)]);
+    assert_eq!(client.list_sessions(&true), vec![&env, mistake.clone(), session_hash(&env, 2)]);
+    assert!(client.check_presence_for(&alice, &mistake));
+    assert_eq!(client.get_attendance_count(&mistake), 1);
+}
+
+#[test]
+fn test_fill_rate() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let hash = session_hash(&env, 1);
+    assert_eq!(client.fill_rate(&hash), None);
+
+    client.set_hash_with_capacity(&admin, &hash, &3);
+    assert_eq!(client.fill_rate(&hash), Some(0));
+
+    client.register(&Address::generate(&env), &hash);
+    assert_eq!(client.fill_rate(&hash), Some(33));
+    client.register(&Address::generate(&env), &hash);
+    client.register(&Address::generate(&env), &hash);
+    assert_eq!(client.fill_rate(&hash), Some(100));
+
+    let unlimited = session_hash(&env, 2);
+    client.set_hash(&admin, &unlimited);
+    client.register(&Address::generate(&env), &unlimited);
+    assert_eq!(client.fill_rate(&unlimited), None);
+}
+
+#[test]
+fn test_opt_out() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.set_profile(&alice, &String::from_str(&env, "alice"), &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert!(!client.is_opted_out(&alice));
+    client.set_opt_out(&alice, &true);
+    assert!(client.is_opted_out(&alice));
+
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&alice, &hash);
+
+    let anonymous = Address::from_str(&env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF");
+    let events = env.events().all();
+    let (_, _, data) = events.get(events.len() - 2).unwrap();
+    let payload: (Address, BytesN<32>, String, u32, u32, u64, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    let zero_receipt = BytesN::from_array(&env, &[0u8; 32]);
+    assert_eq!(payload, (anonymous.clone(), hash.clone(), String::from_str(&env, ""), 1, 1, 10, zero_receipt));
+    let (_, _, data) = events.last().unwrap();
+    let payload: (Address, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (anonymous, hash.clone()));
+    assert!(client.check_presence(&alice));
+    assert_eq!(client.get_attendance_count(&hash), 1);
+
+    client.set_opt_out(&alice, &false);
+    assert!(!client.is_opted_out(&alice));
+}
+
+#[test]
+fn test_opt_out_failure_events() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let nft = env.register(MockBadge, ());
+    client.set_badge_contract(&admin, &nft);
+    MockBadgeClient::new(&env, &nft).set_failing(&true);
+
+    // The contract holds no funds, so the payout fails without reverting
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    client.set_reward_token(&admin, &sac.address(), &100, &false);
+
+    let alice = Address::generate(&env);
+    client.set_opt_out(&alice, &true);
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    client.register(&alice, &hash);
+
+    let anonymous = Address::from_str(&env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF");
+    let events = env.events().all();
+    let (_, _, data) = events
+        .iter()
+        .find(|(_, topics, _)| *topics == (Symbol::new(&env, "reward_fail"), symbol_short!("v1")).into_val(&env))
+        .unwrap();
+    let payload: (Address, Address, i128) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (anonymous.clone(), sac.address(), 100));
+
+    let (_, _, data) = events
+        .iter()
+        .find(|(_, topics, _)| *topics == (Symbol::new(&env, "badge_fail"), symbol_short!("v1")).into_val(&env))
+        .unwrap();
+    let payload: (Address, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (anonymous, hash));
+}
+
+#[test]
+fn test_rotate_hash() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.try_rotate_hash(&admin, &session_hash(&env, 2)), Err(Ok(Error::NoActiveSession)));
+
+    let first = session_hash(&env, 1);
+    client.set_hash_with_capacity(&admin, &first, &2);
+    let alice = Address::generate(&env);
+    client.register(&alice, &first);
+
+    let second = session_hash(&env, 2);
+    client.rotate_hash(&admin, &second);
+    let rotated = env.events().all().iter().any(|(_, topics, data)| {
+        topics == (symbol_short!("rotate"), symbol_short!("v1")).into_val(&env)
+            && <(BytesN<32>, BytesN<32>, u32)>::try_from_val(&env, &data).unwrap() == (first.clone(), second.clone(), 1)
+    });
+    assert!(rotated);
+
+    assert!(client.check_presence(&alice));
+    assert_eq!(client.session_count(), 1);
+    assert_eq!(client.remaining_capacity(), Some(1));
+    assert_eq!(client.try_register(&alice, &second), Err(Ok(Error::AlreadyRegistered)));
+    assert_eq!(client.try_rotate_hash(&admin, &first), Err(Ok(Error::HashAlreadyUsed)));
+
+    // The expiry carries over too
+    env.ledger().set_sequence_number(100);
+    client.set_hash_with_expiry(&admin, &session_hash(&env, 3), &110);
+    client.rotate_hash(&admin, &session_hash(&env, 4));
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.try_register(&alice, &session_hash(&env, 4)), Err(Ok(Error::SessionExpired)));
+}
+
+#[test]
+fn test_admin_history() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.admin_history(), vec![&env, admin.clone()]);
+
+    let second = Address::generate(&env);
+    client.transfer_admin(&second);
+    let third = Address::generate(&env);
+    client.propose_admin(&third);
+    client.accept_admin();
+    assert_eq!(client.admin_history(), vec![&env, admin, second, third]);
+
+    // Only the most recent handoffs are kept
+    for _ in 0..AttendanceContract::MAX_ADMIN_HISTORY {
+        client.transfer_admin(&Address::generate(&env));
+    }
+    let history = client.admin_history();
+    assert_eq!(history.len(), AttendanceContract::MAX_ADMIN_HISTORY);
+    assert_eq!(history.last(), Some(client.get_admin()));
+
+    // Removing the primary records the admin promoted in its place; other removals do not
+    let primary = client.get_admin();
+    let staff = Address::generate(&env);
+    let helper = Address::generate(&env);
+    client.add_admin(&primary, &staff);
+    client.add_admin(&primary, &helper);
+    client.remove_admin(&staff, &helper);
+    assert_eq!(client.admin_history().last(), Some(primary.clone()));
+    client.remove_admin(&staff, &primary);
+    assert_eq!(client.get_admin(), staff);
+    assert_eq!(client.admin_history().last(), Some(staff));
+}
+
+#[test]
+fn test_profile_bio() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_profile_cooldown(&admin, &0);
+
+    let alice = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    let nickname = String::from_str(&env, "Satoshi");
+    let bio = String::from_str(&env, "Stellar ambassador");
+    assert_eq!(client.try_set_bio(&alice, &bio), Err(Ok(Error::ProfileNotFound)));
+
+    // Without a bio the event keeps the v3 shape
+    client.set_profile(&alice, &nickname, &empty, &empty);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("profile"), symbol_short!("v3")).into_val(&env));
+
+    client.set_bio(&alice, &bio);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("profile"), symbol_short!("v4")).into_val(&env));
+    let payload: (Address, String, String, u64, String) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload.4, bio);
+
+    let profile = client.get_profile(&alice).unwrap();
+    assert_eq!(profile.bio, bio);
+    assert_eq!(profile.nickname, nickname);
+
+    let long = String::from_str(&env, &"a".repeat(AttendanceContract::MAX_BIO as usize + 1));
+    assert_eq!(client.try_set_bio(&alice, &long), Err(Ok(Error::InvalidBio)));
+    assert_eq!(client.try_set_profile(&alice, &nickname, &empty, &long), Err(Ok(Error::InvalidBio)));
+}
+
+#[test]
+fn test_initialize_or_get() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    assert_eq!(client.initialize_or_get(&admin, &None), admin);
+    assert_eq!(client.get_admin(), admin);
+
+    // A second call is a no-op that reports the existing admin
+    let other = Address::generate(&env);
+    assert_eq!(client.initialize_or_get(&other, &None), admin);
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.try_initialize(&other, &None), Err(Ok(Error::AlreadyInitialized)));
+}
+
+#[test]
+fn test_register_with_password() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let password = String::from_str(&env, "stellar meetup 2026");
+    let hash: BytesN<32> = env.crypto().sha256(&password.to_bytes()).into();
+    client.set_hash(&admin, &hash);
+
+    let alice = Address::generate(&env);
+    assert_eq!(
+        client.try_register_with_password(&alice, &String::from_str(&env, "wrong phrase")),
+        Err(Ok(Error::IncorrectHash))
+    );
+
+    client.register_with_password(&alice, &password);
+    assert!(client.check_presence(&alice));
+}
+
+#[test]
+fn test_stats() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let empty = Stats { session_count: 0, total_unique_users: 0, total_checkins_all: 0, active: false };
+    assert_eq!(client.stats(), empty);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+    client.register(&bob, &session_hash(&env, 1));
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.register(&alice, &session_hash(&env, 2));
+    assert_eq!(
+        client.stats(),
+        Stats { session_count: 2, total_unique_users: 2, total_checkins_all: 3, active: true }
+    );
+
+    client.deregister(&alice);
+    client.close_session(&admin);
+    assert_eq!(
+        client.stats(),
+        Stats { session_count: 2, total_unique_users: 2, total_checkins_all: 2, active: false }
+    );
+}
+
+#[test]
+fn test_event_prefix() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AttendanceContract, ());
+    let client = AttendanceContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let prefix = symbol_short!("acme");
+    client.initialize(&admin, &Some(prefix.clone()));
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (prefix.clone(), symbol_short!("init"), symbol_short!("v1")).into_val(&env));
+    assert_eq!(client.event_prefix(), Some(prefix.clone()));
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (prefix, symbol_short!("new_sess"), symbol_short!("v3")).into_val(&env));
+
+    // Without a prefix the topics keep their original shape
+    let (plain, _) = setup(&env);
+    assert_eq!(plain.event_prefix(), None);
+}
+
+#[test]
+fn test_can_create_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let moderator = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.add_moderator(&admin, &moderator);
+
+    assert!(client.can_create_session(&admin));
+    assert!(client.can_create_session(&moderator));
+    assert!(!client.can_create_session(&stranger));
+
+    client.remove_moderator(&admin, &moderator);
+    assert!(!client.can_create_session(&moderator));
+}
+
+#[test]
+fn test_admin_clear_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let test_run = session_hash(&env, 1);
+    client.set_hash(&admin, &test_run);
+    client.register(&alice, &test_run);
+    client.register(&bob, &test_run);
+
+    assert_eq!(client.try_admin_clear_session(&admin, &test_run, &false, &10), Err(Ok(Error::CannotPurgeActive)));
+
+    assert_eq!(client.admin_clear_session(&admin, &test_run, &true, &10), 2);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("adm_reset"), symbol_short!("v2")).into_val(&env));
+    let payload: (Address, BytesN<32>, u32, u32) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), test_run.clone(), 2, 0));
+
+    assert!(!client.check_presence(&alice));
+    assert_eq!(client.get_attendance_count(&test_run), 0);
+    assert_eq!(client.get_attendees(&test_run), Vec::new(&env));
+    assert_eq!(client.get_total_checkins(&alice), 0);
+    assert_eq!(client.stats().total_checkins_all, 0);
+    assert!(client.get_session_info(&test_run).is_some());
+
+    // Users can check in again after the reset
+    client.register(&alice, &test_run);
+    assert_eq!(client.get_attendance_count(&test_run), 1);
+}
+
+#[test]
+fn test_admin_clear_session_in_chunks() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let test_run = session_hash(&env, 1);
+    client.set_hash(&admin, &test_run);
+    for _ in 0..12 {
+        client.register(&Address::generate(&env), &test_run);
+    }
+    client.set_hash(&admin, &session_hash(&env, 2));
+
+    // Each call handles at most 10 attendees, whatever the caller asks for
+    assert_eq!(client.admin_clear_session(&admin, &test_run, &false, &100), 10);
+    assert!(env.cost_estimate().resources().write_entries <= MAX_WRITE_ENTRIES);
+    assert_eq!(client.get_attendance_count(&test_run), 2);
+    assert_eq!(client.get_attendees(&test_run).len(), 2);
+
+    assert_eq!(client.admin_clear_session(&admin, &test_run, &false, &100), 2);
+    assert_eq!(client.get_attendance_count(&test_run), 0);
+    assert_eq!(client.stats().total_checkins_all, 0);
+}
+
+#[test]
+fn test_receipt() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+    env.ledger().set_sequence_number(77);
+    client.register(&alice, &hash);
+
+    let mut preimage = alice.clone().to_xdr(&env);
+    preimage.append(&Bytes::from(hash.clone()));
+    preimage.append(&Bytes::from_array(&env, &77u32.to_be_bytes()));
+    let expected: BytesN<32> = env.crypto().sha256(&preimage).into();
+
+    let events = env.events().all();
+    let (_, _, data) = events.get(events.len() - 2).unwrap();
+    let payload: (Address, BytesN<32>, String, u32, u32, u64, BytesN<32>) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload.6, expected);
+    assert_eq!(client.get_receipt(&alice, &hash), Some(expected));
+
+    // Admin marks carry no receipt, and withdrawing drops it
+    let bob = Address::generate(&env);
+    client.admin_mark_present(&admin, &bob);
+    assert_eq!(client.get_receipt(&bob, &hash), None);
+    client.deregister(&alice);
+    assert_eq!(client.get_receipt(&alice, &hash), None);
+}
+
+#[test]
+fn test_extend_session() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(client.try_extend_session(&admin, &10), Err(Ok(Error::NoActiveSession)));
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    assert_eq!(client.try_extend_session(&admin, &10), Err(Ok(Error::InvalidConfig)));
+
+    let hash = session_hash(&env, 2);
+    env.ledger().set_sequence_number(100);
+    client.set_hash_with_expiry(&admin, &hash, &110);
+    assert_eq!(client.try_extend_session(&admin, &0), Err(Ok(Error::InvalidConfig)));
+
+    client.extend_session(&admin, &20);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("extend"), symbol_short!("v1")).into_val(&env));
+    let payload: (Address, BytesN<32>, u32) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(payload, (admin.clone(), hash.clone(), 130));
+
+    env.ledger().set_sequence_number(125);
+    client.register(&Address::generate(&env), &hash);
+
+    env.ledger().set_sequence_number(131);
+    assert_eq!(client.try_extend_session(&admin, &10), Err(Ok(Error::SessionExpired)));
+}
+
+#[test]
+fn test_nickname_available() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let satoshi = String::from_str(&env, "Satoshi");
+    assert!(client.nickname_available(&satoshi));
+
+    client.set_profile(&Address::generate(&env), &satoshi, &String::from_str(&env, ""), &String::from_str(&env, ""));
+    assert!(!client.nickname_available(&satoshi));
+
+    assert!(!client.nickname_available(&String::from_str(&env, "ab")));
+    assert!(!client.nickname_available(&String::from_str(&env, " Hal")));
+    assert!(client.nickname_available(&String::from_str(&env, "Hal")));
+}
 
 #[test]
-fn test() {
+fn test_profiles_locked() {
     let env = Env::default();
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
+    client.set_profile_cooldown(&admin, &0);
+
+    let alice = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    client.set_profile(&alice, &String::from_str(&env, "Satoshi"), &empty, &empty);
+
+    assert!(!client.profiles_locked());
+    client.set_profiles_locked(&admin, &true);
+    assert!(client.profiles_locked());
 
-    let words = client.hello(&String::from_str(&env, "Dev"));
     assert_eq!(
-        words,
-        vec![
-            &env,
-            String::from_str(&env, "Hello"),
-            String::from_str(&env, "Dev"),
-        ]
+        client.try_set_profile(&alice, &String::from_str(&env, "Hal"), &empty, &empty),
+        Err(Ok(Error::ProfilesLocked))
     );
+    assert_eq!(client.try_set_bio(&alice, &String::from_str(&env, "bio")), Err(Ok(Error::ProfilesLocked)));
+    assert_eq!(client.try_delete_profile(&alice), Err(Ok(Error::ProfilesLocked)));
+
+    // Unlike a pause, check-ins still go through
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+
+    client.set_profiles_locked(&admin, &false);
+    client.set_bio(&alice, &String::from_str(&env, "bio"));
+}
+
+#[test]
+fn test_sessions_attended() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_max_sessions_retained(&admin, &3);
+
+    let alice = Address::generate(&env);
+    assert_eq!(client.sessions_attended(&alice), 0);
+
+    client.set_hash(&admin, &session_hash(&env, 1));
+    client.register(&alice, &session_hash(&env, 1));
+    client.set_hash(&admin, &session_hash(&env, 2));
+    client.register(&alice, &session_hash(&env, 2));
+
+    // A rotated code of the same event does not count twice
+    client.rotate_hash(&admin, &session_hash(&env, 3));
+    assert_eq!(client.sessions_attended(&alice), 2);
+
+    // Evicting the first session drops it from the count, but not from the lifetime total
+    client.set_hash(&admin, &session_hash(&env, 4));
+    assert_eq!(client.sessions_attended(&alice), 1);
+    assert_eq!(client.get_total_checkins(&alice), 2);
+}
+
+#[test]
+fn test_reregister_earns_points_once() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let hash = session_hash(&env, 1);
+    client.set_hash(&admin, &hash);
+
+    for _ in 0..6 {
+        client.register(&alice, &hash);
+        client.deregister(&alice);
+    }
+    client.register(&alice, &hash);
+
+    assert_eq!(client.get_points(&alice), 10);
+    assert_eq!(client.get_total_checkins(&alice), 1);
 }